@@ -1,30 +1,24 @@
+use maze_core::{Direction, GameState, Outcome, BOMB, EXIT, PLAYER, WALL};
 use rand::Rng;
 use std::io;
 use std::io::prelude::*;
 
 const MAP_SIZE: usize = 10;
-const WALL: char = '#';
-const PLAYER: char = 'P';
-const EXIT: char = 'E';
-const BOMB: char = '*';
 
 fn init_map() -> Vec<Vec<char>> {
     let mut map = vec![vec![' '; MAP_SIZE]; MAP_SIZE];
     // set walls
-    for i in 0..MAP_SIZE {
-        map[0][i] = WALL;
-        map[MAP_SIZE - 1][i] = WALL;
-        map[i][0] = WALL;
-        map[i][MAP_SIZE - 1] = WALL;
+    for (i, row) in map.iter_mut().enumerate() {
+        if i == 0 || i == MAP_SIZE - 1 {
+            row.fill(WALL);
+        }
+        row[0] = WALL;
+        row[MAP_SIZE - 1] = WALL;
     }
     // set exit
     let mut rng = rand::thread_rng();
     let exit_row = rng.gen_range(1..MAP_SIZE - 1);
     let exit_col = rng.gen_range(1..MAP_SIZE - 1);
-    while map[exit_row][exit_col] != ' ' {
-        let exit_row = rng.gen_range(1..MAP_SIZE - 1);
-        let exit_col = rng.gen_range(1..MAP_SIZE - 1);
-    }
     map[exit_row][exit_col] = EXIT;
     // set player
     let mut player_row = rng.gen_range(1..MAP_SIZE - 1);
@@ -45,28 +39,27 @@ fn init_map() -> Vec<Vec<char>> {
     map
 }
 
-
-fn play_game() {
-    let mut map = init_map();
-    let mut player_row = 0;
-    let mut player_col = 0;
-    // find player and exit
-    for i in 1..MAP_SIZE - 1 {
-        for j in 1..MAP_SIZE - 1 {
-            if map[i][j] == PLAYER {
-                player_row = i;
-                player_col = j;
+fn print_map(game: &GameState) {
+    for y in 0..game.height() {
+        for x in 0..game.width() {
+            let cell = game.map[x][y];
+            if (x, y) == game.player {
+                print!("{}", PLAYER);
+            } else if cell == PLAYER {
+                print!(" ");
+            } else {
+                print!("{}", cell);
             }
         }
+        println!();
     }
+}
+
+fn play_game() {
+    let mut game = GameState::from_map(init_map());
     loop {
         // print map
-        for row in &map {
-            for cell in row {
-                print!("{}", cell);
-            }
-            println!();
-        }
+        print_map(&game);
         // get user input
         let mut input = String::new();
         print!("Enter a direction (up, down, left, right): ");
@@ -74,81 +67,27 @@ fn play_game() {
         io::stdin().read_line(&mut input).unwrap();
         input = input.trim().to_string();
         // process input
-        let mut new_player_row = player_row;
-        let mut new_player_col = player_col;
-        match input.as_str() {
-            "up" => {
-                new_player_row -= 1;
-                if new_player_row < 1 || map[new_player_row][new_player_col] == WALL {
-                    continue;
-                }
-                if map[new_player_row][new_player_col] == BOMB {
-                    println!("You hit a bomb! Game over!");
-                    return;
-                }
-                map[player_row][player_col] = ' ';
-                player_row = new_player_row;
-                if map[player_row][player_col] == EXIT {
-                    println!("Congratulations! You escaped the maze!");
-                    return;
-                }
-                map[player_row][player_col] = PLAYER;
-            }
-            "down" => {
-                new_player_row += 1;
-                if new_player_row >= MAP_SIZE - 1 || map[new_player_row][new_player_col] == WALL {
-                    continue;
-                }
-                if map[new_player_row][new_player_col] == BOMB {
-                    println!("You hit a bomb! Game over!");
-                    return;
-                }
-                map[player_row][player_col] = ' ';
-                player_row = new_player_row;
-                if map[player_row][player_col] == EXIT {
-                    println!("Congratulations! You escaped the maze!");
-                    return;
-                }
-                map[player_row][player_col] = PLAYER;
-            }
-            "left" => {
-                new_player_col -= 1;
-                if new_player_col < 1 || map[new_player_row][new_player_col] == WALL {
-                    continue;
-                }
-                if map[new_player_row][new_player_col] == BOMB {
-                    println!("You hit a bomb! Game over!");
-                    return;
-                }
-                map[player_row][player_col] = ' ';
-                player_col = new_player_col;
-                if map[player_row][player_col] == EXIT {
-                    println!("Congratulations! You escaped the maze!");
-                    return;
-                }
-                map[player_row][player_col] = PLAYER;
-            }
-            "right" => {
-                new_player_col += 1;
-                if new_player_col >= MAP_SIZE - 1 || map[new_player_row][new_player_col] == WALL {
-                    continue;
-                }
-                if map[new_player_row][new_player_col] == BOMB {
-                    println!("You hit a bomb! Game over!");
-                    return;
-                }
-                map[player_row][player_col] = ' ';
-                player_col = new_player_col;
-                if map[player_row][player_col] == EXIT {
-                    println!("Congratulations! You escaped the maze!");
-                    return;
-                }
-                map[player_row][player_col] = PLAYER;
-            }
+        let dir = match input.as_str() {
+            "up" => Direction::Up,
+            "down" => Direction::Down,
+            "left" => Direction::Left,
+            "right" => Direction::Right,
             _ => {
                 println!("Invalid input. Try again.");
                 continue;
             }
+        };
+        game.step(dir);
+        match game.outcome {
+            Outcome::Bombed => {
+                println!("You hit a bomb! Game over!");
+                return;
+            }
+            Outcome::Escaped => {
+                println!("Congratulations! You escaped the maze!");
+                return;
+            }
+            Outcome::Playing => {}
         }
     }
 }
@@ -156,5 +95,3 @@ fn play_game() {
 fn main() {
     play_game();
 }
-
-
@@ -0,0 +1,33 @@
+// cargo bench --bench generate [-- <size>...]
+// 큰 미로에서 각 알고리즘의 생성 시간을 잰다. 기본 크기는 1000, 2000.
+// carve 는 길을 파는 시간만, generate 는 다시 만들기와 도달 확인, 문과 폭탄 (normal 난이도) 까지 포함한 시간이다
+use std::time::Instant;
+
+use maze_core::{generator, Difficulty, GameState};
+
+use oorandom::Rand32;
+
+fn main() {
+    let mut sizes: Vec<usize> = std::env::args().skip(1).filter_map(|arg| arg.parse().ok()).collect();
+    if sizes.is_empty() {
+        sizes = vec![1000, 2000];
+    }
+    for size in sizes {
+        for name in generator::ALGORITHMS {
+            let generator = generator::by_name(name).unwrap();
+            let mut maze = vec![vec!['#'; size]; size];
+            let mut rng = Rand32::new(size as u64);
+            let start = Instant::now();
+            generator.carve(&mut maze, (1, 1), &mut rng);
+            let elapsed = start.elapsed();
+            let open = maze.iter().flatten().filter(|&&c| c == ' ').count();
+            println!("{:>5}x{:<5} {:<14} carve    {:>10.2?} ({} open cells)", size, size, generator.name(), elapsed, open);
+
+            let difficulty = Difficulty::Normal;
+            let start = Instant::now();
+            let game = GameState::generate(size as u64, generator.as_ref(), size, size, difficulty.bombs(size, size), difficulty.doors());
+            let elapsed = start.elapsed();
+            println!("{:>5}x{:<5} {:<14} generate {:>10.2?} ({} bombs)", size, size, generator.name(), elapsed, game.bombs.len());
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// 미로를 돌아다니는 적. 자리와 순찰 길은 seed 로 정하고 움직일 때는 난수를 쓰지 않으므로,
+// 같은 미로에서 플레이어가 같은 때 같은 곳에 있으면 서버에서든 혼자 하는 판에서든 똑같이 움직인다.
+// 적에게 닿으면 폭탄에 맞은 것처럼 그 판이 끝난다
+use oorandom::Rand32;
+
+use crate::solver;
+use crate::{fov, Direction, GameState};
+
+type Pos = (usize, usize);
+
+// 미로를 만든 난수열과 겹치지 않도록 다른 증분을 쓴다
+const SPAWN_STREAM: u64 = 0x656e_656d_7900;
+// 시작 칸에서 이만큼 넘게 걸어야 닿는 곳에만 놓는다
+const SPAWN_DISTANCE: usize = 10;
+// Patrol 이 오가는 길의 최대 칸 수
+const PATROL_LENGTH: usize = 12;
+// Ambush 는 플레이어가 출구로 가는 길에서 이만큼 앞을 막으러 간다
+const AMBUSH_LEAD: usize = 4;
+// 큰 미로에서도 한 걸음이 싸도록 길은 가까이에서만 찾는다. 보이는 플레이어와 집 (순찰 길, 기다리는 자리) 은
+// sight 의 SEARCH_FACTOR 배 걸음 안에서, Chase 는 CHASE_RANGE 걸음 안에서 찾고 그보다 멀면 곧장 그쪽으로 다가간다
+const SEARCH_FACTOR: usize = 4;
+const CHASE_RANGE: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Behavior {
+    // 정해진 길을 오가다 플레이어가 보이면 쫓는다
+    Patrol,
+    // 어디 있든 가장 가까운 플레이어를 쫓는다
+    Chase,
+    // 제자리에서 기다리다 플레이어가 보이면 앞길을 막으러 간다
+    Ambush,
+}
+
+pub const BEHAVIORS: [Behavior; 3] = [Behavior::Patrol, Behavior::Chase, Behavior::Ambush];
+
+impl Behavior {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Behavior::Patrol => "patrol",
+            Behavior::Chase => "chase",
+            Behavior::Ambush => "ambush",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        BEHAVIORS.into_iter().find(|behavior| behavior.name() == name)
+    }
+}
+
+// 적은 step_ms (Go 부터, 혼자 할 때는 시작부터) 마다 한 칸 움직이고, sight 칸 안에서 벽에 가리지 않은 플레이어를 본다
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnemyRules {
+    pub step_ms: u32,
+    pub sight: u8,
+}
+
+impl Default for EnemyRules {
+    fn default() -> Self {
+        EnemyRules { step_ms: 400, sight: 6 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Enemy {
+    pub pos: Pos,
+    pub behavior: Behavior,
+    // Patrol 은 이 길을 오가고, Ambush 는 route[0] 에서 기다린다. next 는 다음에 갈 route 의 칸
+    route: Vec<Pos>,
+    next: usize,
+    forward: bool,
+}
+
+impl Enemy {
+    pub fn new(behavior: Behavior, pos: Pos) -> Self {
+        Enemy { pos, behavior, route: vec![pos], next: 0, forward: true }
+    }
+
+    // 한 칸 움직이거나 (길이 없으면) 그대로 있는다. players 는 아직 뛰고 있는 사람들의 위치
+    pub fn step(&mut self, map: &[Vec<char>], players: &[Pos], exit: Pos, rules: &EnemyRules) {
+        let range = SEARCH_FACTOR * (rules.sight as usize).max(1);
+        let target = if self.behavior == Behavior::Chase {
+            nearest(self.pos, players.iter().copied())
+        } else {
+            let sight = fov::look(map, self.pos, rules.sight as usize);
+            nearest(self.pos, players.iter().copied().filter(|&player| sight.sees(player)))
+        };
+        let goal = match (self.behavior, target) {
+            (Behavior::Ambush, Some(player)) => self.ambush_point(map, player, exit, range),
+            (_, Some(player)) => player,
+            (Behavior::Patrol, None) => self.patrol_goal(),
+            (_, None) => self.route[0],
+        };
+        let limit = if self.behavior == Behavior::Chase { CHASE_RANGE } else { range };
+        match solver::bfs_within(map, self.pos, goal, limit) {
+            Some(path) => {
+                if let Some(&next) = path.get(1) {
+                    self.pos = next;
+                }
+            }
+            None if self.behavior == Behavior::Chase => {
+                if let Some(next) = target.and_then(|player| approach(map, self.pos, player)) {
+                    self.pos = next;
+                }
+            }
+            // 쫓다가 집에서 너무 멀어졌으면 지금 자리를 새 집으로 삼는다
+            None if target.is_none() => {
+                self.route = vec![self.pos];
+                self.next = 0;
+                self.forward = true;
+            }
+            None => {}
+        }
+    }
+
+    // 순찰 길 끝에 닿으면 돌아선다
+    fn patrol_goal(&mut self) -> Pos {
+        if self.pos == self.route[self.next] && self.route.len() > 1 {
+            if self.next + 1 == self.route.len() {
+                self.forward = false;
+            } else if self.next == 0 {
+                self.forward = true;
+            }
+            self.next = if self.forward { self.next + 1 } else { self.next - 1 };
+        }
+        self.route[self.next]
+    }
+
+    // 플레이어가 출구로 가는 길에서 AMBUSH_LEAD 칸 앞. 이미 그 자리거나 플레이어가 코앞이거나
+    // 출구가 range 걸음보다 멀어 길을 모르면 플레이어에게 간다
+    fn ambush_point(&self, map: &[Vec<char>], player: Pos, exit: Pos, range: usize) -> Pos {
+        let close = self.pos.0.abs_diff(player.0) + self.pos.1.abs_diff(player.1) <= 2;
+        match solver::bfs_within(map, player, exit, range) {
+            Some(path) if !close => {
+                let lead = path[AMBUSH_LEAD.min(path.len() - 1)];
+                if lead == self.pos { player } else { lead }
+            }
+            _ => player,
+        }
+    }
+}
+
+// 맨해튼 거리로 가장 가까운 위치. 같으면 (x, y) 가 작은 쪽
+fn nearest(from: Pos, players: impl Iterator<Item = Pos>) -> Option<Pos> {
+    players.min_by_key(|&p| (p.0.abs_diff(from.0) + p.1.abs_diff(from.1), p))
+}
+
+// 이웃 칸 가운데 target 까지 맨해튼 거리가 줄어드는 곳 (같으면 (x, y) 가 작은 쪽). 없으면 None
+fn approach(map: &[Vec<char>], from: Pos, target: Pos) -> Option<Pos> {
+    let distance = |p: Pos| p.0.abs_diff(target.0) + p.1.abs_diff(target.1);
+    [Direction::Up, Direction::Right, Direction::Down, Direction::Left]
+        .iter()
+        .filter_map(|dir| {
+            let (dx, dy) = dir.offset();
+            let (x, y) = (from.0 as i32 + dx, from.1 as i32 + dy);
+            let inside = x >= 0 && y >= 0 && (x as usize) < map.len() && (y as usize) < map[0].len();
+            inside.then_some((x as usize, y as usize))
+        })
+        .filter(|&next| solver::is_open(map, next) && distance(next) < distance(from))
+        .min_by_key(|&next| (distance(next), next))
+}
+
+// behaviors 마다 적 하나를 seed 로 정한 자리에 놓는다. 놓을 곳이 모자라면 그만큼 덜 놓는다
+pub fn spawn(game: &GameState, behaviors: &[Behavior], seed: u64) -> Vec<Enemy> {
+    let mut rng = Rand32::new_inc(seed, SPAWN_STREAM);
+    let distances = solver::distances(&game.map, game.player);
+    // 문 너머라 시작 칸에서 닿지 않는 곳도 괜찮다
+    let mut cells: Vec<Pos> = (0..game.width())
+        .flat_map(|x| (0..game.height()).map(move |y| (x, y)))
+        .filter(|&(x, y)| game.map[x][y] == ' ' && distances[x][y].is_none_or(|d| d > SPAWN_DISTANCE))
+        .collect();
+    let mut enemies = Vec::new();
+    for &behavior in behaviors {
+        if cells.is_empty() {
+            break;
+        }
+        let pos = cells.swap_remove(rng.rand_range(0..cells.len() as u32) as usize);
+        let mut enemy = Enemy::new(behavior, pos);
+        if behavior == Behavior::Patrol && !cells.is_empty() {
+            let end = cells[rng.rand_range(0..cells.len() as u32) as usize];
+            if let Some(mut path) = solver::bfs(&game.map, pos, end) {
+                path.truncate(PATROL_LENGTH);
+                enemy.route = path;
+            }
+        }
+        enemies.push(enemy);
+    }
+    enemies
+}
+
+// 누군가 pos 에서 적과 만났는지
+pub fn caught(enemies: &[Enemy], pos: Pos) -> bool {
+    enemies.iter().any(|enemy| enemy.pos == pos)
+}
@@ -0,0 +1,122 @@
+// 어두운 미로에서 플레이어가 볼 수 있는 칸. 재귀 그림자 드리우기 (recursive shadowcasting) 로
+// 여덟 팔분면을 한 줄씩 훑으며, 벽이나 닫힌 문이 가리는 기울기 구간은 다음 줄부터 건너뛴다
+use crate::solver;
+use crate::WALL;
+
+type Pos = (usize, usize);
+
+// 팔분면의 (열, 줄) 을 (dx, dy) 로 옮기는 계수 (xx, xy, yx, yy)
+const OCTANTS: [(i32, i32, i32, i32); 8] = [
+    (1, 0, 0, 1),
+    (0, 1, 1, 0),
+    (0, -1, 1, 0),
+    (-1, 0, 0, 1),
+    (-1, 0, 0, -1),
+    (0, -1, -1, 0),
+    (0, 1, -1, 0),
+    (1, 0, 0, -1),
+];
+
+// 빛을 가리는 칸. 열쇠와 폭탄 너머는 보인다
+pub fn is_opaque(map: &[Vec<char>], pos: Pos) -> bool {
+    let cell = map[pos.0][pos.1];
+    cell == WALL || solver::door_index(cell).is_some()
+}
+
+// origin 에서 radius 칸 (원) 안이면서 가리지 않은 칸. 가리는 벽 자체도 보인다. visible[x][y] 로 본다
+pub fn visible(map: &[Vec<char>], origin: Pos, radius: usize) -> Vec<Vec<bool>> {
+    let sight = look(map, origin, radius);
+    let mut visible = vec![vec![false; map[0].len()]; map.len()];
+    for (x, column) in visible.iter_mut().enumerate() {
+        for (y, lit) in column.iter_mut().enumerate() {
+            *lit = sight.sees((x, y));
+        }
+    }
+    visible
+}
+
+// visible 과 같지만 origin 둘레 radius 칸만 담는다. 큰 미로에서 적이 볼 때마다 미로 크기만큼 만들지 않도록
+pub fn look(map: &[Vec<char>], origin: Pos, radius: usize) -> Sight {
+    let side = 2 * radius + 1;
+    let mut caster = Caster { map, origin, radius: radius as i32, seen: vec![vec![false; side]; side] };
+    caster.seen[radius][radius] = true;
+    for octant in OCTANTS {
+        caster.cast(1, 1.0, 0.0, octant);
+    }
+    Sight { origin, radius, seen: caster.seen }
+}
+
+pub struct Sight {
+    origin: Pos,
+    radius: usize,
+    // seen[radius + dx][radius + dy] 가 origin 에서 (dx, dy) 떨어진 칸
+    seen: Vec<Vec<bool>>,
+}
+
+impl Sight {
+    pub fn sees(&self, pos: Pos) -> bool {
+        let (x, y) = (pos.0 + self.radius, pos.1 + self.radius);
+        if x < self.origin.0 || y < self.origin.1 {
+            return false;
+        }
+        let (x, y) = (x - self.origin.0, y - self.origin.1);
+        x < self.seen.len() && y < self.seen.len() && self.seen[x][y]
+    }
+}
+
+struct Caster<'a> {
+    map: &'a [Vec<char>],
+    origin: Pos,
+    radius: i32,
+    seen: Vec<Vec<bool>>,
+}
+
+impl Caster<'_> {
+    // row 줄부터 기울기 start..end 사이를 밝힌다. 벽을 만나면 그 앞 구간은 다음 줄로 넘기고 벽 뒤에서 다시 시작한다
+    fn cast(&mut self, row: i32, mut start: f32, end: f32, octant: (i32, i32, i32, i32)) {
+        if start < end {
+            return;
+        }
+        let (xx, xy, yx, yy) = octant;
+        let (width, height) = (self.map.len() as i32, self.map[0].len() as i32);
+        let mut next_start = start;
+        for distance in row..=self.radius {
+            let dy = -distance;
+            let mut blocked = false;
+            for dx in -distance..=0 {
+                let left = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+                let right = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+                if start < right {
+                    continue;
+                }
+                if end > left {
+                    break;
+                }
+                let x = self.origin.0 as i32 + dx * xx + dy * xy;
+                let y = self.origin.1 as i32 + dx * yx + dy * yy;
+                // 미로 밖은 벽으로 본다
+                let inside = x >= 0 && y >= 0 && x < width && y < height;
+                if inside && dx * dx + dy * dy <= self.radius * self.radius {
+                    let r = self.radius;
+                    self.seen[(x - self.origin.0 as i32 + r) as usize][(y - self.origin.1 as i32 + r) as usize] = true;
+                }
+                let opaque = !inside || is_opaque(self.map, (x as usize, y as usize));
+                if blocked {
+                    if opaque {
+                        next_start = right;
+                        continue;
+                    }
+                    blocked = false;
+                    start = next_start;
+                } else if opaque && distance < self.radius {
+                    blocked = true;
+                    self.cast(distance + 1, start, left, octant);
+                    next_start = right;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+}
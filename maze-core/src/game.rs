@@ -0,0 +1,334 @@
+use oorandom::Rand32;
+
+use crate::enemy::Behavior;
+use crate::generator::MazeGenerator;
+use crate::solver::{self, Strategy};
+use crate::{BOMB, DOORS, EXIT, KEYS, PLAYER, WALL};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Playing,
+    Escaped,
+    Bombed,
+}
+
+// 폭탄은 Go (혼자 할 때는 시작) 뒤 fuse_ms 가 지나면 터져 둘레 radius 칸 (대각선 포함) 의 벽을 부수고
+// 그 안에 있는 플레이어를 잡는다
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BombRules {
+    pub fuse_ms: u32,
+    pub radius: u8,
+}
+
+impl Default for BombRules {
+    fn default() -> Self {
+        BombRules { fuse_ms: 30_000, radius: 2 }
+    }
+}
+
+// 메뉴에서 고르는 난이도. 미로가 클수록 폭탄도 많아지고, 어려울수록 열쇠를 모아 열어야 할 문과 적이 늘어난다
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+pub const DIFFICULTIES: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+impl Difficulty {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        DIFFICULTIES.into_iter().find(|difficulty| difficulty.name() == name)
+    }
+
+    // 미로 칸 몇 개마다 폭탄 하나. 아무리 작아도 하나는 놓는다
+    pub fn bombs(&self, width: usize, height: usize) -> usize {
+        let cells_per_bomb = match self {
+            Difficulty::Easy => 400,
+            Difficulty::Normal => 150,
+            Difficulty::Hard => 50,
+        };
+        (width * height / cells_per_bomb).max(1)
+    }
+
+    // 열쇠와 문 짝의 수. 길이 짧으면 generate 가 더 적게 놓는다
+    pub fn doors(&self) -> usize {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => KEYS.len(),
+        }
+    }
+
+    pub fn enemies(&self) -> &'static [Behavior] {
+        match self {
+            Difficulty::Easy => &[],
+            Difficulty::Normal => &[Behavior::Patrol],
+            Difficulty::Hard => &[Behavior::Patrol, Behavior::Chase, Behavior::Ambush],
+        }
+    }
+}
+
+// 한 판의 상태. map[x][y] 로 접근하고, 위치는 (x, y) 이다
+#[derive(Clone, Debug)]
+pub struct GameState {
+    pub map: Vec<Vec<char>>,
+    pub player: (usize, usize),
+    pub bombs: Vec<(usize, usize)>,
+    pub exit: (usize, usize),
+    // 주운 열쇠. 주운 순서대로
+    pub keys: Vec<char>,
+    pub seed: Option<u64>,
+    pub outcome: Outcome,
+}
+
+impl GameState {
+    // 같은 seed 와 폭탄, 문 수는 항상 같은 벽, 플레이어, 열쇠와 문, 폭탄, 출구 배치를 만든다
+    pub fn generate(seed: u64, generator: &dyn MazeGenerator, width: usize, height: usize, bombs: usize, doors: usize) -> Self {
+        let mut rng = Rand32::new(seed);
+        let mut state = generate_map(&mut rng, generator, width, height);
+        place_doors(&mut rng, &mut state, doors);
+        place_bombs(&mut rng, &mut state, bombs);
+        state.seed = Some(seed);
+        state
+    }
+
+    // 이미 만들어진 미로(예: 네트워크로 받은 것)에서 P, *, E 위치를 찾는다.
+    // 문은 남았는데 짝 열쇠가 바닥에 없으면 (다시 접속해 받은 자기 미로) 이미 주운 것이다
+    pub fn from_map(map: Vec<Vec<char>>) -> Self {
+        let find = |target: char| {
+            map.iter()
+                .enumerate()
+                .find_map(|(x, column)| column.iter().position(|&c| c == target).map(|y| (x, y)))
+        };
+        let exit = find(EXIT).unwrap_or((map.len() - 1, map[0].len() - 1));
+        let player = find(PLAYER).unwrap_or((0, 0));
+        let bombs = (0..map.len())
+            .flat_map(|x| (0..map[x].len()).map(move |y| (x, y)))
+            .filter(|&(x, y)| map[x][y] == BOMB)
+            .collect();
+        let keys = (0..KEYS.len())
+            .filter(|&i| map.iter().flatten().any(|&c| c == DOORS[i]) && !map.iter().flatten().any(|&c| c == KEYS[i]))
+            .map(|i| KEYS[i])
+            .collect();
+        GameState { map, player, bombs, exit, keys, seed: None, outcome: Outcome::Playing }
+    }
+
+    pub fn width(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.map[0].len()
+    }
+
+    // 벽, 열쇠가 없는 문, 미로 밖이면 None
+    pub fn neighbor(&self, pos: (usize, usize), dir: Direction) -> Option<(usize, usize)> {
+        let (dx, dy) = dir.offset();
+        let (x, y) = (pos.0 as i32 + dx, pos.1 as i32 + dy);
+        if x < 0 || y < 0 || x >= self.width() as i32 || y >= self.height() as i32 {
+            return None;
+        }
+        let (x, y) = (x as usize, y as usize);
+        if self.map[x][y] == WALL {
+            return None;
+        }
+        if solver::door_index(self.map[x][y]).is_some_and(|i| !self.keys.contains(&KEYS[i])) {
+            return None;
+        }
+        Some((x, y))
+    }
+
+    // 움직였으면 true. 출구나 폭탄 칸에 닿으면 outcome 이 바뀐다
+    pub fn step(&mut self, dir: Direction) -> bool {
+        if self.outcome != Outcome::Playing {
+            return false;
+        }
+        match self.neighbor(self.player, dir) {
+            Some(pos) => {
+                self.enter(pos);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 플레이어를 pos 로 옮긴다. 열쇠는 줍고, 한 번 연 문은 열어 둔다.
+    // 이동을 서버가 판정할 때는 클라이언트가 이것으로 자기 미로를 맞춘다
+    pub fn enter(&mut self, pos: (usize, usize)) {
+        let cell = self.map[pos.0][pos.1];
+        if solver::key_index(cell).is_some() {
+            self.keys.push(cell);
+            self.map[pos.0][pos.1] = ' ';
+        } else if solver::door_index(cell).is_some() {
+            self.map[pos.0][pos.1] = ' ';
+        }
+        self.player = pos;
+        self.outcome = self.outcome_at(pos);
+    }
+
+    // 플레이어에서 출구까지의 경로 (폭탄은 피한다). 닫힌 문이 남아 있으면 전략과 상관없이
+    // 열쇠를 주우며 가는 넓이 우선 탐색을 쓴다
+    pub fn solve(&self, strategy: Strategy) -> Option<Vec<(usize, usize)>> {
+        if self.map.iter().flatten().any(|&cell| DOORS.contains(&cell)) {
+            return solver::bfs_with_keys(&self.map, self.player, self.exit, &self.keys);
+        }
+        solver::solve(&self.map, self.player, self.exit, strategy)
+    }
+
+    // bomb 칸과 폭발 범위의 벽을 길로 바꾼다. 출구는 남는다
+    pub fn explode(&mut self, bomb: (usize, usize), radius: u8) {
+        let (width, height) = (self.width(), self.height());
+        let r = radius as usize;
+        let (bx, by) = bomb;
+        for x in bx.saturating_sub(r)..=(bx + r).min(width - 1) {
+            for y in by.saturating_sub(r)..=(by + r).min(height - 1) {
+                if matches!(self.map[x][y], WALL | BOMB) {
+                    self.map[x][y] = ' ';
+                }
+            }
+        }
+        let caught = self.player.0.abs_diff(bx) <= r && self.player.1.abs_diff(by) <= r;
+        if self.outcome == Outcome::Playing && caught {
+            self.outcome = Outcome::Bombed;
+        }
+    }
+
+    pub fn outcome_at(&self, pos: (usize, usize)) -> Outcome {
+        match self.map[pos.0][pos.1] {
+            EXIT => Outcome::Escaped,
+            BOMB => Outcome::Bombed,
+            _ => Outcome::Playing,
+        }
+    }
+}
+
+fn generate_map(rng: &mut Rand32, generator: &dyn MazeGenerator, width: usize, height: usize) -> GameState {
+    // 출구에 닿지 못하는 미로가 나오면 재귀 대신 같은 rng 로 다시 만든다
+    loop {
+        // 미로를 나타내는 2차원 벡터 생성
+        let mut map = vec![vec![WALL; height]; width];
+
+        let exit = (width - 1, height - 1);
+        map[exit.0][exit.1] = EXIT;
+        // set player
+        let mut player = (
+            (rng.rand_range(0..width as u32) as usize).max(1),
+            (rng.rand_range(0..height as u32) as usize).max(1),
+        );
+        while map[player.0][player.1] != WALL {
+            player = (rng.rand_range(0..width as u32) as usize, rng.rand_range(0..height as u32) as usize);
+        }
+        map[player.0][player.1] = PLAYER;
+
+        generator.carve(&mut map, player, rng);
+
+        if is_reachable(&map, player, exit) {
+            return GameState { map, player, bombs: Vec::new(), exit, keys: Vec::new(), seed: None, outcome: Outcome::Playing };
+        }
+    }
+}
+
+// 출구로 가는 가장 짧은 길 위에 문을 고르게 놓고, i 번 열쇠는 i-1 번 문을 열어야만 닿는 곳 (첫 열쇠는
+// 시작 쪽) 에 숨긴다. 그래서 열쇠를 차례대로 모아야 나갈 수 있다. 고리가 있는 미로에서는 문을 돌아가는
+// 길이 있을 수 있는데, 그 너머에 열쇠를 둘 곳이 없으면 남은 문은 치운다
+fn place_doors(rng: &mut Rand32, state: &mut GameState, count: usize) {
+    let path = match solver::bfs(&state.map, state.player, state.exit) {
+        Some(path) => path,
+        None => return,
+    };
+    // 문 사이에 적어도 몇 칸은 있도록
+    let count = count.min(KEYS.len()).min(path.len() / 4);
+    let doors: Vec<(usize, usize)> = (1..=count).map(|i| path[path.len() * i / (count + 1)]).collect();
+    for (i, &(x, y)) in doors.iter().enumerate() {
+        state.map[x][y] = DOORS[i];
+    }
+    let (width, height) = (state.width(), state.height());
+    let mut on_path = vec![vec![false; height]; width];
+    for &(x, y) in &path {
+        on_path[x][y] = true;
+    }
+    for i in 0..count {
+        let region = solver::reachable(&state.map, state.player, &KEYS[..i]);
+        let before = (i > 0).then(|| solver::reachable(&state.map, state.player, &KEYS[..i - 1]));
+        let cells: Vec<(usize, usize)> = (0..width)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .filter(|&(x, y)| state.map[x][y] == ' ' && region[x][y] && !before.as_ref().is_some_and(|before| before[x][y]))
+            .collect();
+        // 되도록 출구로 가는 길에서 벗어난 곳에
+        let aside: Vec<(usize, usize)> = cells.iter().copied().filter(|&(x, y)| !on_path[x][y]).collect();
+        let choices = if aside.is_empty() { &cells } else { &aside };
+        if choices.is_empty() {
+            for &(x, y) in &doors[i..] {
+                state.map[x][y] = ' ';
+            }
+            return;
+        }
+        let (x, y) = choices[rng.rand_range(0..choices.len() as u32) as usize];
+        state.map[x][y] = KEYS[i];
+    }
+}
+
+// 폭탄은 길 위에만 놓는다. 열쇠를 주우며 출구로 가는 가장 짧은 길을 한 번 찾아 두고 그 길 밖에만 놓으므로
+// 놓을 때마다 다시 찾지 않아도 길은 막히지 않는다. 빈 칸이 모자라면 놓을 수 있는 만큼만 놓는다
+fn place_bombs(rng: &mut Rand32, state: &mut GameState, count: usize) {
+    let (width, height) = (state.width(), state.height());
+    let count = count.min(width * height);
+    let mut on_path = vec![vec![false; height]; width];
+    match solver::bfs_with_keys(&state.map, state.player, state.exit, &[]) {
+        Some(path) => {
+            for (x, y) in path {
+                on_path[x][y] = true;
+            }
+        }
+        None => return,
+    }
+    let mut tries = count.saturating_mul(20);
+    while state.bombs.len() < count && tries > 0 {
+        tries -= 1;
+        let (x, y) = (rng.rand_range(0..width as u32) as usize, rng.rand_range(0..height as u32) as usize);
+        if state.map[x][y] != ' ' || on_path[x][y] {
+            continue;
+        }
+        state.map[x][y] = BOMB;
+        state.bombs.push((x, y));
+    }
+    // 마지막으로 한 번만 확인한다. 막혔으면 폭탄 없이 둔다
+    if !is_reachable(&state.map, state.player, state.exit) {
+        for (x, y) in state.bombs.drain(..) {
+            state.map[x][y] = ' ';
+        }
+    }
+}
+
+// 바닥의 열쇠를 주워 문을 열면서 갈 수 있는지
+pub fn is_reachable(maze: &[Vec<char>], start: (usize, usize), goal: (usize, usize)) -> bool {
+    solver::bfs_with_keys(maze, start, goal, &[]).is_some()
+}
@@ -0,0 +1,519 @@
+use std::collections::HashMap;
+
+use oorandom::Rand32;
+
+// 미로 생성 알고리즘. 모든 구현은 시작 칸과 같은 홀짝(parity)을 가진 칸들을
+// 2칸 간격의 격자로 보고, 그 격자를 잇는 통로를 '#' 위에 파낸다.
+pub trait MazeGenerator {
+    fn name(&self) -> &'static str;
+    fn carve(&self, maze: &mut Vec<Vec<char>>, start: (usize, usize), rng: &mut Rand32);
+}
+
+pub const ALGORITHMS: [&str; 9] = [
+    "dfs",
+    "prim",
+    "kruskal",
+    "wilson",
+    "aldous-broder",
+    "eller",
+    "hunt-and-kill",
+    "growing-tree",
+    "division",
+];
+
+pub fn by_name(name: &str) -> Option<Box<dyn MazeGenerator>> {
+    match name {
+        "dfs" => Some(Box::new(Backtracker)),
+        "prim" => Some(Box::new(Prim)),
+        "kruskal" => Some(Box::new(Kruskal)),
+        "wilson" => Some(Box::new(Wilson)),
+        "aldous-broder" => Some(Box::new(AldousBroder)),
+        "eller" => Some(Box::new(Eller)),
+        "hunt-and-kill" => Some(Box::new(HuntAndKill)),
+        "growing-tree" => Some(Box::new(GrowingTree)),
+        "division" => Some(Box::new(RecursiveDivision)),
+        _ => None,
+    }
+}
+
+pub fn shuffle<T>(items: &mut [T], rng: &mut Rand32) {
+    for i in (1..items.len()).rev() {
+        let j = rng.rand_range(0..(i as u32 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+fn pick<T: Copy>(items: &[T], rng: &mut Rand32) -> T {
+    items[rng.rand_range(0..items.len() as u32) as usize]
+}
+
+#[derive(Default)]
+struct Neighbors {
+    ids: [usize; 4],
+    len: usize,
+}
+
+impl Neighbors {
+    fn push(&mut self, id: usize) {
+        self.ids[self.len] = id;
+        self.len += 1;
+    }
+    fn filter(self, keep: impl Fn(&usize) -> bool) -> Neighbors {
+        let mut list = Neighbors::default();
+        for id in self.as_slice() {
+            if keep(id) {
+                list.push(*id);
+            }
+        }
+        list
+    }
+    fn as_slice(&self) -> &[usize] {
+        &self.ids[..self.len]
+    }
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// 2칸 간격 격자. id 는 격자 안에서의 row-major 번호
+struct Grid {
+    r0: usize,
+    c0: usize,
+    rows: usize,
+    cols: usize,
+    start: usize,
+    usable: Vec<bool>,
+}
+
+impl Grid {
+    fn new(maze: &[Vec<char>], start: (usize, usize)) -> Self {
+        let first = |p: usize| if p % 2 == 1 { 1 } else { p.min(2) };
+        let (r0, c0) = (first(start.0), first(start.1));
+        let rows = (maze.len() - r0).div_ceil(2);
+        let cols = (maze[0].len() - c0).div_ceil(2);
+        let start_id = (start.0 - r0) / 2 * cols + (start.1 - c0) / 2;
+        let mut grid = Grid { r0, c0, rows, cols, start: start_id, usable: vec![false; rows * cols] };
+
+        // 폭탄/출구처럼 이미 채워진 칸은 건너뛰고, 시작 칸과 이어질 수 있는 칸만 쓴다
+        let mut stack = vec![start_id];
+        grid.usable[start_id] = true;
+        while let Some(id) = stack.pop() {
+            for &n in grid.neighbors(id).as_slice() {
+                let (r, c) = grid.pos(n);
+                if !grid.usable[n] && maze[r][c] == '#' {
+                    grid.usable[n] = true;
+                    stack.push(n);
+                }
+            }
+        }
+        grid
+    }
+
+    fn len(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    fn pos(&self, id: usize) -> (usize, usize) {
+        (self.r0 + id / self.cols * 2, self.c0 + id % self.cols * 2)
+    }
+
+    // 큰 미로에서 칸마다 Vec 을 만들지 않도록 고정 크기 배열로 돌려준다
+    fn neighbors(&self, id: usize) -> Neighbors {
+        let (i, j) = (id / self.cols, id % self.cols);
+        let mut list = Neighbors::default();
+        if i > 0 { list.push(id - self.cols); }
+        if i + 1 < self.rows { list.push(id + self.cols); }
+        if j > 0 { list.push(id - 1); }
+        if j + 1 < self.cols { list.push(id + 1); }
+        list
+    }
+
+    fn usable_neighbors(&self, id: usize) -> Neighbors {
+        self.neighbors(id).filter(|&n| self.usable[n])
+    }
+
+    fn open(&self, maze: &mut [Vec<char>], id: usize) {
+        let (r, c) = self.pos(id);
+        if maze[r][c] == '#' {
+            maze[r][c] = ' ';
+        }
+    }
+
+    fn carve(&self, maze: &mut [Vec<char>], a: usize, b: usize) {
+        let ((ra, ca), (rb, cb)) = (self.pos(a), self.pos(b));
+        self.open(maze, a);
+        self.open(maze, b);
+        if maze[(ra + rb) / 2][(ca + cb) / 2] == '#' {
+            maze[(ra + rb) / 2][(ca + cb) / 2] = ' ';
+        }
+    }
+}
+
+// 기존의 백트래킹(DFS). 칸마다 재귀하면 큰 미로에서 스택이 넘치므로 명시적 스택을 쓴다
+pub struct Backtracker;
+
+impl MazeGenerator for Backtracker {
+    fn name(&self) -> &'static str {
+        "dfs"
+    }
+    fn carve(&self, maze: &mut Vec<Vec<char>>, start: (usize, usize), rng: &mut Rand32) {
+        dfs(maze, start.0, start.1, rng);
+    }
+}
+
+fn dfs(maze: &mut [Vec<char>], row: usize, col: usize, rng: &mut Rand32) {
+    let mut stack = vec![(row, col, shuffled_directions(rng), 0)];
+    while let Some(top) = stack.last_mut() {
+        if top.3 == top.2.len() {
+            stack.pop();
+            continue;
+        }
+        let (row, col) = (top.0, top.1);
+        let (dr, dc) = top.2[top.3];
+        top.3 += 1;
+        let (r, c) = (row as i32 + dr, col as i32 + dc);
+        if r < 1 || r >= maze.len() as i32 || c < 1 || c >= maze[0].len() as i32 {
+            continue;
+        }
+        let (r, c) = (r as usize, c as usize);
+        if maze[r][c] == '#' {
+            maze[(row as i32 + dr / 2) as usize][(col as i32 + dc / 2) as usize] = ' ';
+            maze[r][c] = ' ';
+            stack.push((r, c, shuffled_directions(rng), 0));
+        }
+    }
+}
+
+fn shuffled_directions(rng: &mut Rand32) -> [(i32, i32); 4] {
+    let mut directions = [(0, -2), (0, 2), (-2, 0), (2, 0)];
+    shuffle(&mut directions, rng);
+    directions
+}
+
+pub struct Prim;
+
+impl MazeGenerator for Prim {
+    fn name(&self) -> &'static str {
+        "prim"
+    }
+    fn carve(&self, maze: &mut Vec<Vec<char>>, start: (usize, usize), rng: &mut Rand32) {
+        let grid = Grid::new(maze, start);
+        let mut visited = vec![false; grid.len()];
+        let mut in_frontier = vec![false; grid.len()];
+        let mut frontier = Vec::new();
+
+        visited[grid.start] = true;
+        for &n in grid.usable_neighbors(grid.start).as_slice() {
+            in_frontier[n] = true;
+            frontier.push(n);
+        }
+        while !frontier.is_empty() {
+            let cell = frontier.swap_remove(rng.rand_range(0..frontier.len() as u32) as usize);
+            let inside = grid.usable_neighbors(cell).filter(|&n| visited[n]);
+            grid.carve(maze, pick(inside.as_slice(), rng), cell);
+            visited[cell] = true;
+            for &n in grid.usable_neighbors(cell).as_slice() {
+                if !visited[n] && !in_frontier[n] {
+                    in_frontier[n] = true;
+                    frontier.push(n);
+                }
+            }
+        }
+    }
+}
+
+pub struct Kruskal;
+
+impl MazeGenerator for Kruskal {
+    fn name(&self) -> &'static str {
+        "kruskal"
+    }
+    fn carve(&self, maze: &mut Vec<Vec<char>>, start: (usize, usize), rng: &mut Rand32) {
+        let grid = Grid::new(maze, start);
+        let mut edges = Vec::new();
+        for id in 0..grid.len() {
+            for &n in grid.usable_neighbors(id).as_slice() {
+                if grid.usable[id] && n > id {
+                    edges.push((id, n));
+                }
+            }
+        }
+        shuffle(&mut edges, rng);
+
+        let mut parent: Vec<usize> = (0..grid.len()).collect();
+        fn find(parent: &mut [usize], mut x: usize) -> usize {
+            while parent[x] != x {
+                parent[x] = parent[parent[x]];
+                x = parent[x];
+            }
+            x
+        }
+        for (a, b) in edges {
+            let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+            if ra != rb {
+                parent[ra] = rb;
+                grid.carve(maze, a, b);
+            }
+        }
+    }
+}
+
+pub struct Wilson;
+
+impl MazeGenerator for Wilson {
+    fn name(&self) -> &'static str {
+        "wilson"
+    }
+    fn carve(&self, maze: &mut Vec<Vec<char>>, start: (usize, usize), rng: &mut Rand32) {
+        let grid = Grid::new(maze, start);
+        let mut visited = vec![false; grid.len()];
+        let mut next = vec![usize::MAX; grid.len()];
+        visited[grid.start] = true;
+
+        let mut order: Vec<usize> = (0..grid.len()).filter(|&id| grid.usable[id]).collect();
+        shuffle(&mut order, rng);
+        for cell in order {
+            // loop-erased random walk: 같은 칸을 다시 지나면 next 가 덮어써져 고리가 지워진다
+            let mut cur = cell;
+            while !visited[cur] {
+                let n = pick(grid.usable_neighbors(cur).as_slice(), rng);
+                next[cur] = n;
+                cur = n;
+            }
+            let mut cur = cell;
+            while !visited[cur] {
+                visited[cur] = true;
+                grid.carve(maze, cur, next[cur]);
+                cur = next[cur];
+            }
+        }
+    }
+}
+
+pub struct AldousBroder;
+
+impl MazeGenerator for AldousBroder {
+    fn name(&self) -> &'static str {
+        "aldous-broder"
+    }
+    fn carve(&self, maze: &mut Vec<Vec<char>>, start: (usize, usize), rng: &mut Rand32) {
+        let grid = Grid::new(maze, start);
+        let mut visited = vec![false; grid.len()];
+        let mut remaining = grid.usable.iter().filter(|&&u| u).count() - 1;
+        let mut cur = grid.start;
+        visited[cur] = true;
+
+        while remaining > 0 {
+            let n = pick(grid.usable_neighbors(cur).as_slice(), rng);
+            if !visited[n] {
+                grid.carve(maze, cur, n);
+                visited[n] = true;
+                remaining -= 1;
+            }
+            cur = n;
+        }
+    }
+}
+
+pub struct Eller;
+
+impl MazeGenerator for Eller {
+    fn name(&self) -> &'static str {
+        "eller"
+    }
+    fn carve(&self, maze: &mut Vec<Vec<char>>, start: (usize, usize), rng: &mut Rand32) {
+        let grid = Grid::new(maze, start);
+        let mut set = vec![usize::MAX; grid.len()];
+        let mut next_set = 0;
+
+        for i in 0..grid.rows {
+            let row: Vec<usize> = (i * grid.cols..(i + 1) * grid.cols).collect();
+            let last = i + 1 == grid.rows;
+            for &id in &row {
+                if grid.usable[id] && set[id] == usize::MAX {
+                    set[id] = next_set;
+                    next_set += 1;
+                }
+            }
+
+            // 가로로 이웃한 서로 다른 집합을 무작위로 합친다 (마지막 줄은 모두 합친다)
+            for pair in row.windows(2) {
+                let (a, b) = (pair[0], pair[1]);
+                if !grid.usable[a] || !grid.usable[b] || set[a] == set[b] {
+                    continue;
+                }
+                if last || rng.rand_range(0..2) == 0 {
+                    let (from, to) = (set[b], set[a]);
+                    for &id in &row {
+                        if set[id] == from {
+                            set[id] = to;
+                        }
+                    }
+                    grid.carve(maze, a, b);
+                }
+            }
+            if last {
+                break;
+            }
+
+            // 각 집합은 아래 줄로 최소 한 번 내려간다
+            let mut groups: Vec<Vec<usize>> = Vec::new();
+            let mut group_of = HashMap::new();
+            for &id in &row {
+                if !grid.usable[id] || !grid.usable[id + grid.cols] {
+                    continue;
+                }
+                let index = *group_of.entry(set[id]).or_insert_with(|| {
+                    groups.push(Vec::new());
+                    groups.len() - 1
+                });
+                groups[index].push(id);
+            }
+            for mut group in groups {
+                shuffle(&mut group, rng);
+                let count = 1 + rng.rand_range(0..group.len() as u32) as usize;
+                for &id in &group[..count] {
+                    set[id + grid.cols] = set[id];
+                    grid.carve(maze, id, id + grid.cols);
+                }
+            }
+        }
+    }
+}
+
+pub struct HuntAndKill;
+
+impl MazeGenerator for HuntAndKill {
+    fn name(&self) -> &'static str {
+        "hunt-and-kill"
+    }
+    fn carve(&self, maze: &mut Vec<Vec<char>>, start: (usize, usize), rng: &mut Rand32) {
+        let grid = Grid::new(maze, start);
+        let mut visited = vec![false; grid.len()];
+        let mut cur = grid.start;
+        let mut scan_from = 0;
+        visited[cur] = true;
+
+        loop {
+            let fresh = grid.usable_neighbors(cur).filter(|&n| !visited[n]);
+            if !fresh.is_empty() {
+                let n = pick(fresh.as_slice(), rng);
+                grid.carve(maze, cur, n);
+                visited[n] = true;
+                cur = n;
+                continue;
+            }
+
+            // hunt: 방문한 칸과 맞닿은 첫 미방문 칸을 찾아 거기서 다시 걷는다.
+            // scan_from 앞쪽은 모두 끝난 칸이므로 다시 훑지 않는다
+            while scan_from < grid.len() && (!grid.usable[scan_from] || visited[scan_from]) {
+                scan_from += 1;
+            }
+            let hunted = (scan_from..grid.len()).find_map(|id| {
+                if !grid.usable[id] || visited[id] {
+                    return None;
+                }
+                let seen = grid.usable_neighbors(id).filter(|&n| visited[n]);
+                if seen.is_empty() { None } else { Some((id, seen)) }
+            });
+            match hunted {
+                Some((id, seen)) => {
+                    grid.carve(maze, pick(seen.as_slice(), rng), id);
+                    visited[id] = true;
+                    cur = id;
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+pub struct GrowingTree;
+
+impl MazeGenerator for GrowingTree {
+    fn name(&self) -> &'static str {
+        "growing-tree"
+    }
+    fn carve(&self, maze: &mut Vec<Vec<char>>, start: (usize, usize), rng: &mut Rand32) {
+        let grid = Grid::new(maze, start);
+        let mut visited = vec![false; grid.len()];
+        let mut active = vec![grid.start];
+        visited[grid.start] = true;
+
+        // 절반은 가장 최근 칸(백트래킹), 절반은 무작위 칸(Prim)을 골라 섞인 질감을 만든다
+        while !active.is_empty() {
+            let index = if rng.rand_range(0..2) == 0 {
+                active.len() - 1
+            } else {
+                rng.rand_range(0..active.len() as u32) as usize
+            };
+            let cell = active[index];
+            let fresh = grid.usable_neighbors(cell).filter(|&n| !visited[n]);
+            if fresh.is_empty() {
+                active.swap_remove(index);
+                continue;
+            }
+            let n = pick(fresh.as_slice(), rng);
+            grid.carve(maze, cell, n);
+            visited[n] = true;
+            active.push(n);
+        }
+    }
+}
+
+pub struct RecursiveDivision;
+
+impl MazeGenerator for RecursiveDivision {
+    fn name(&self) -> &'static str {
+        "division"
+    }
+    fn carve(&self, maze: &mut Vec<Vec<char>>, start: (usize, usize), rng: &mut Rand32) {
+        let grid = Grid::new(maze, start);
+        // 모든 통로를 열어 둔 뒤 벽을 세워 나간다
+        let mut right = vec![true; grid.len()];
+        let mut down = vec![true; grid.len()];
+
+        let mut regions = vec![(0, 0, grid.rows, grid.cols)];
+        while let Some((i0, j0, h, w)) = regions.pop() {
+            if h < 2 && w < 2 {
+                continue;
+            }
+            let horizontal = if h != w { h > w } else { rng.rand_range(0..2) == 0 };
+            if horizontal {
+                let k = i0 + rng.rand_range(0..(h - 1) as u32) as usize;
+                let gap = j0 + rng.rand_range(0..w as u32) as usize;
+                for j in j0..j0 + w {
+                    if j != gap {
+                        down[k * grid.cols + j] = false;
+                    }
+                }
+                regions.push((i0, j0, k - i0 + 1, w));
+                regions.push((k + 1, j0, i0 + h - k - 1, w));
+            } else {
+                let k = j0 + rng.rand_range(0..(w - 1) as u32) as usize;
+                let gap = i0 + rng.rand_range(0..h as u32) as usize;
+                for i in i0..i0 + h {
+                    if i != gap {
+                        right[i * grid.cols + k] = false;
+                    }
+                }
+                regions.push((i0, j0, h, k - j0 + 1));
+                regions.push((i0, k + 1, h, j0 + w - k - 1));
+            }
+        }
+
+        for id in 0..grid.len() {
+            if !grid.usable[id] {
+                continue;
+            }
+            grid.open(maze, id);
+            let (i, j) = (id / grid.cols, id % grid.cols);
+            if j + 1 < grid.cols && right[id] && grid.usable[id + 1] {
+                grid.carve(maze, id, id + 1);
+            }
+            if i + 1 < grid.rows && down[id] && grid.usable[id + grid.cols] {
+                grid.carve(maze, id, id + grid.cols);
+            }
+        }
+    }
+}
@@ -0,0 +1,20 @@
+// ggez 없이 돌아가는 미로 규칙: 생성, 이동/충돌, 승패 판정, 시야, 적.
+// GUI(mazegame), 터미널 프로토타입, 서버가 함께 쓴다.
+pub mod enemy;
+pub mod fov;
+pub mod generator;
+pub mod protocol;
+pub mod solver;
+mod game;
+
+pub use enemy::{Behavior, EnemyRules};
+pub use game::{is_reachable, BombRules, Difficulty, Direction, GameState, Outcome, DIFFICULTIES};
+pub use solver::Strategy;
+
+pub const WALL: char = '#';
+pub const PLAYER: char = 'P';
+pub const EXIT: char = 'E';
+pub const BOMB: char = '*';
+// 열쇠와 그 열쇠로 여는 문. 같은 자리의 소문자와 대문자가 짝이다
+pub const KEYS: [char; 3] = ['a', 'b', 'c'];
+pub const DOORS: [char; 3] = ['A', 'B', 'C'];
@@ -0,0 +1,564 @@
+// 멀티플레이 메시지. 한 프레임은
+//   길이(u32 BE, 종류 바이트부터 끝까지) | 종류(u8) | 본문
+// 이고, 연결하자마자 양쪽이 Hello 를 주고받아 버전을 맞춘다.
+// 미로와 위치, 승패는 서버가 정하고 클라이언트는 Move 로 가고 싶은 방향만 보낸다.
+// 접속하면 로비에 있고, 방을 만들거나(CreateRoom) 들어가면(JoinRoom) 그 방의 판에 참가한다.
+// 클라이언트는 살아 있다는 표시로 Ping 을 계속 보내고, 판 도중 끊기면 Welcome 에서 받은
+// token 으로 잠시 동안 같은 자리에 Rejoin 할 수 있다. Watch 로 들어온 사람은 방의 메시지를 모두 받지만 움직일 수 없다
+use std::io::{self, Read, Write};
+
+use crate::enemy::BEHAVIORS;
+use crate::{Behavior, Direction};
+
+pub const VERSION: u16 = 15;
+pub const MAGIC: [u8; 4] = *b"MAZE";
+pub const DEFAULT_PORT: u16 = 8088;
+// 서버가 LAN 에 Beacon 을 뿌리는 UDP 포트
+pub const DISCOVERY_PORT: u16 = 8089;
+const BEACON_MAGIC: [u8; 4] = *b"MZBC";
+// 한 datagram 에 넣을 방 수
+const BEACON_ROOMS: usize = 16;
+// 2000x2000 마라톤 미로 (칸마다 1바이트) 가 MazeData 하나에 들어가고도 남는 크기
+pub const MAX_FRAME: usize = 1 << 23;
+
+const HELLO: u8 = 0;
+const MAZE_DATA: u8 = 1;
+const PLAYER_MOVE: u8 = 2;
+const PLAYER_FINISHED: u8 = 3;
+const BOMBED: u8 = 4;
+const CHAT: u8 = 5;
+const PING: u8 = 6;
+const PONG: u8 = 7;
+const MOVE: u8 = 8;
+const WELCOME: u8 = 9;
+const CREATE_ROOM: u8 = 10;
+const GAME_OVER: u8 = 11;
+const LIST_ROOMS: u8 = 12;
+const ROOM_LIST: u8 = 13;
+const JOIN_ROOM: u8 = 14;
+const LEAVE_ROOM: u8 = 15;
+const REFUSED: u8 = 16;
+const REJOIN: u8 = 17;
+const PLAYER_AWAY: u8 = 18;
+const PLAYER_BACK: u8 = 19;
+const PLAYER_LEFT: u8 = 20;
+const WATCH: u8 = 21;
+const READY: u8 = 22;
+const COUNTDOWN: u8 = 23;
+const GO: u8 = 24;
+const REMATCH: u8 = 25;
+const REMATCH_VOTE: u8 = 26;
+const FUSE: u8 = 27;
+const EXPLODED: u8 = 28;
+const SIGHT: u8 = 29;
+const ENEMIES: u8 = 30;
+
+// Finish 종류
+const FINISH_ESCAPED: u8 = 0;
+const FINISH_BOMBED: u8 = 1;
+const FINISH_LEFT: u8 = 2;
+
+// 방 이름은 길이(u8) 앞머리를 붙여 보내므로 이보다 길면 자른다
+pub const MAX_NAME: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoomInfo {
+    pub name: String,
+    pub players: u8,
+    pub capacity: u8,
+    pub playing: bool,
+    pub width: u16,
+    pub height: u16,
+}
+
+// 한 사람이 판을 어떻게 끝냈는지. 시간은 서버가 Go 부터 잰다
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Finish {
+    Escaped { time_ms: u32 },
+    Bombed,
+    // 끝나기 전에 나갔거나 돌아오지 않았다
+    Left,
+}
+
+impl Finish {
+    fn key(&self) -> (u8, u32) {
+        match *self {
+            Finish::Escaped { time_ms } => (0, time_ms),
+            Finish::Bombed => (1, 0),
+            Finish::Left => (2, 0),
+        }
+    }
+}
+
+// place 는 1 부터. 같은 시간에 들어왔으면 같은 순위이고 다음 순위는 그만큼 건너뛴다 (1, 1, 3)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Placing {
+    pub player: u8,
+    pub place: u8,
+    pub finish: Finish,
+}
+
+// finishes[i] 는 i 번 플레이어. 탈출한 사람은 빠른 순서, 폭탄에 맞은 사람, 나간 사람 순이다
+pub fn placings(finishes: &[Finish]) -> Vec<Placing> {
+    let mut placings: Vec<Placing> = finishes
+        .iter()
+        .enumerate()
+        .map(|(player, finish)| Placing {
+            player: player as u8,
+            place: 1 + finishes.iter().filter(|other| other.key() < finish.key()).count() as u8,
+            finish: *finish,
+        })
+        .collect();
+    placings.sort_by_key(|placing| (placing.place, placing.player));
+    placings
+}
+
+// 서버가 주기적으로 UDP 로 알리는 자기 소개. 길이 앞머리 없이 datagram 하나에 담는다
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Beacon {
+    pub version: u16,
+    pub name: String,
+    // 접속할 TCP 포트
+    pub port: u16,
+    pub players: u16,
+    pub rooms: Vec<RoomInfo>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Hello { version: u16 },
+    // 클라이언트 -> 서버: 로비
+    ListRooms,
+    // players 명이 모이면 시작한다
+    CreateRoom { room: String, players: u8, seed: u64, algorithm: String, width: u16, height: u16, bombs: u16, doors: u8, enemies: Vec<Behavior> },
+    JoinRoom { room: String },
+    // 자리에 앉지 않고 구경만 한다
+    Watch { room: String },
+    Rejoin { token: u64 },
+    LeaveRoom,
+    // 서버 -> 클라이언트: 로비
+    RoomList { rooms: Vec<RoomInfo> },
+    Refused { reason: String },
+    // 서버 -> 클라이언트: 방에 들어왔고, 그 방에서 내 플레이어 번호
+    Welcome { player: u8, token: u64 },
+    // 서버 -> 클라이언트: 누가 끊겼다(Away), 돌아왔다(Back), 끝내 안 돌아왔거나 나갔다(Left)
+    PlayerAway { player: u8 },
+    PlayerBack { player: u8 },
+    PlayerLeft { player: u8 },
+    // map[x][y] 를 x 순서대로 펼쳐서 보낸다
+    MazeData { map: Vec<Vec<char>> },
+    // 클라이언트 -> 서버: 미로를 받았다. 모두 Ready 하면 서버가 Countdown 을 세고 Go 를 보낸다
+    Ready,
+    // 서버 -> 클라이언트: Go 까지 남은 초. Go 전에 보낸 Move 는 무시한다
+    Countdown { seconds: u8 },
+    Go,
+    // 서버 -> 클라이언트: 폭탄이 터지기까지 남은 시간 (Go 전이면 Go 부터 잰다) 과 폭발 범위
+    Fuse { ms: u32, radius: u8 },
+    // 서버 -> 클라이언트: (x, y) 의 폭탄이 터졌다. 잡힌 사람은 Bombed 로 따로 알린다
+    Exploded { x: u16, y: u16, radius: u8 },
+    // 서버 -> 클라이언트: 어두운 미로에서 보이는 거리 (칸). 0 이면 미로 전체가 보인다
+    Sight { radius: u8 },
+    // 서버 -> 클라이언트: 적이 움직일 때마다 모든 적의 종류와 위치
+    Enemies { enemies: Vec<(Behavior, u16, u16)> },
+    // 클라이언트 -> 서버
+    Move { dir: Direction },
+    // 서버 -> 클라이언트: 확인된 위치와 결과
+    PlayerMove { player: u8, x: u16, y: u16 },
+    PlayerFinished { player: u8, time_ms: u32 },
+    Bombed { player: u8, x: u16, y: u16 },
+    // 모두 끝나면 서버가 정한 순위를 보낸다
+    GameOver { results: Vec<Placing> },
+    // 클라이언트 -> 서버: 같은 방에서 한 판 더. 서버 -> 클라이언트: 모두 원해서 방을 처음 상태로 되돌렸다
+    Rematch,
+    // 서버 -> 클라이언트: 누가 한 판 더 하고 싶어 한다
+    RematchVote { player: u8 },
+    Chat { text: String },
+    Ping { nonce: u32 },
+    Pong { nonce: u32 },
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+// 본문을 앞에서부터 읽는다. 모자라면 InvalidData
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.buf.len() < n {
+            return Err(invalid("message is truncated"));
+        }
+        let (head, rest) = self.buf.split_at(n);
+        self.buf = rest;
+        Ok(head)
+    }
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+    fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+    fn name(&mut self) -> io::Result<String> {
+        let len = self.u8()? as usize;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| invalid("name is not utf-8"))
+    }
+    // 문자열은 본문의 나머지 전부
+    fn string(&mut self) -> io::Result<String> {
+        String::from_utf8(self.take(self.buf.len())?.to_vec()).map_err(|_| invalid("text is not utf-8"))
+    }
+    fn room(&mut self) -> io::Result<RoomInfo> {
+        let name = self.name()?;
+        Ok(RoomInfo {
+            name,
+            players: self.u8()?,
+            capacity: self.u8()?,
+            playing: self.u8()? != 0,
+            width: self.u16()?,
+            height: self.u16()?,
+        })
+    }
+    fn placing(&mut self) -> io::Result<Placing> {
+        let (player, place) = (self.u8()?, self.u8()?);
+        let finish = match self.u8()? {
+            FINISH_ESCAPED => Finish::Escaped { time_ms: self.u32()? },
+            FINISH_BOMBED => Finish::Bombed,
+            FINISH_LEFT => Finish::Left,
+            other => return Err(invalid(format!("unknown finish {}", other))),
+        };
+        Ok(Placing { player, place, finish })
+    }
+    fn behavior(&mut self) -> io::Result<Behavior> {
+        let code = self.u8()?;
+        BEHAVIORS.get(code as usize).copied().ok_or_else(|| invalid(format!("unknown enemy behavior {}", code)))
+    }
+    fn finish(&self) -> io::Result<()> {
+        if self.buf.is_empty() { Ok(()) } else { Err(invalid("trailing bytes after message")) }
+    }
+}
+
+// MAX_NAME 글자까지만
+pub fn short_name(name: &str) -> String {
+    name.chars().take(MAX_NAME).collect()
+}
+
+// BEHAVIORS 에서의 순서
+fn behavior_code(behavior: &Behavior) -> u8 {
+    BEHAVIORS.iter().position(|b| b == behavior).unwrap_or(0) as u8
+}
+
+fn push_name(body: &mut Vec<u8>, name: &str) {
+    let name = short_name(name);
+    body.push(name.len() as u8);
+    body.extend_from_slice(name.as_bytes());
+}
+
+fn push_room(body: &mut Vec<u8>, room: &RoomInfo) {
+    push_name(body, &room.name);
+    body.extend_from_slice(&[room.players, room.capacity, room.playing as u8]);
+    body.extend_from_slice(&room.width.to_be_bytes());
+    body.extend_from_slice(&room.height.to_be_bytes());
+}
+
+impl Beacon {
+    // 방이 많으면 BEACON_ROOMS 개까지만 싣는다
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = BEACON_MAGIC.to_vec();
+        body.extend_from_slice(&self.version.to_be_bytes());
+        push_name(&mut body, &self.name);
+        body.extend_from_slice(&self.port.to_be_bytes());
+        body.extend_from_slice(&self.players.to_be_bytes());
+        let rooms = &self.rooms[..self.rooms.len().min(BEACON_ROOMS)];
+        body.push(rooms.len() as u8);
+        for room in rooms {
+            push_room(&mut body, room);
+        }
+        body
+    }
+
+    pub fn decode(datagram: &[u8]) -> io::Result<Beacon> {
+        let mut r = Reader { buf: datagram };
+        if r.take(4)? != BEACON_MAGIC {
+            return Err(invalid("not a maze game beacon"));
+        }
+        let version = r.u16()?;
+        let name = r.name()?;
+        let port = r.u16()?;
+        let players = r.u16()?;
+        let count = r.u8()?;
+        let mut rooms = Vec::new();
+        for _ in 0..count {
+            rooms.push(r.room()?);
+        }
+        r.finish()?;
+        Ok(Beacon { version, name, port, players, rooms })
+    }
+}
+
+impl Message {
+    // 길이 앞머리까지 붙인 한 프레임
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        match self {
+            Message::Hello { version } => {
+                body.push(HELLO);
+                body.extend_from_slice(&MAGIC);
+                body.extend_from_slice(&version.to_be_bytes());
+            }
+            Message::Welcome { player, token } => {
+                body.extend_from_slice(&[WELCOME, *player]);
+                body.extend_from_slice(&token.to_be_bytes());
+            }
+            Message::Rejoin { token } => {
+                body.push(REJOIN);
+                body.extend_from_slice(&token.to_be_bytes());
+            }
+            Message::PlayerAway { player } => body.extend_from_slice(&[PLAYER_AWAY, *player]),
+            Message::PlayerBack { player } => body.extend_from_slice(&[PLAYER_BACK, *player]),
+            Message::PlayerLeft { player } => body.extend_from_slice(&[PLAYER_LEFT, *player]),
+            Message::ListRooms => body.push(LIST_ROOMS),
+            Message::CreateRoom { room, players, seed, algorithm, width, height, bombs, doors, enemies } => {
+                body.push(CREATE_ROOM);
+                push_name(&mut body, room);
+                body.push(*players);
+                body.extend_from_slice(&seed.to_be_bytes());
+                body.extend_from_slice(&width.to_be_bytes());
+                body.extend_from_slice(&height.to_be_bytes());
+                body.extend_from_slice(&bombs.to_be_bytes());
+                body.push(*doors);
+                body.push(enemies.len() as u8);
+                body.extend(enemies.iter().map(behavior_code));
+                body.extend_from_slice(algorithm.as_bytes());
+            }
+            Message::JoinRoom { room } => {
+                body.push(JOIN_ROOM);
+                push_name(&mut body, room);
+            }
+            Message::Watch { room } => {
+                body.push(WATCH);
+                push_name(&mut body, room);
+            }
+            Message::LeaveRoom => body.push(LEAVE_ROOM),
+            Message::RoomList { rooms } => {
+                body.push(ROOM_LIST);
+                body.extend_from_slice(&(rooms.len() as u16).to_be_bytes());
+                for room in rooms {
+                    push_room(&mut body, room);
+                }
+            }
+            Message::Refused { reason } => {
+                body.push(REFUSED);
+                body.extend_from_slice(reason.as_bytes());
+            }
+            Message::MazeData { map } => {
+                body.push(MAZE_DATA);
+                body.extend_from_slice(&(map.len() as u16).to_be_bytes());
+                body.extend_from_slice(&(map.first().map_or(0, Vec::len) as u16).to_be_bytes());
+                body.extend(map.iter().flatten().map(|&c| c as u8));
+            }
+            Message::Move { dir } => {
+                let dir = match dir {
+                    Direction::Up => 0,
+                    Direction::Down => 1,
+                    Direction::Left => 2,
+                    Direction::Right => 3,
+                };
+                body.extend_from_slice(&[MOVE, dir]);
+            }
+            Message::PlayerMove { player, x, y } | Message::Bombed { player, x, y } => {
+                body.push(if matches!(self, Message::PlayerMove { .. }) { PLAYER_MOVE } else { BOMBED });
+                body.push(*player);
+                body.extend_from_slice(&x.to_be_bytes());
+                body.extend_from_slice(&y.to_be_bytes());
+            }
+            Message::PlayerFinished { player, time_ms } => {
+                body.extend_from_slice(&[PLAYER_FINISHED, *player]);
+                body.extend_from_slice(&time_ms.to_be_bytes());
+            }
+            Message::Ready => body.push(READY),
+            Message::Countdown { seconds } => body.extend_from_slice(&[COUNTDOWN, *seconds]),
+            Message::Go => body.push(GO),
+            Message::Fuse { ms, radius } => {
+                body.push(FUSE);
+                body.extend_from_slice(&ms.to_be_bytes());
+                body.push(*radius);
+            }
+            Message::Exploded { x, y, radius } => {
+                body.push(EXPLODED);
+                body.extend_from_slice(&x.to_be_bytes());
+                body.extend_from_slice(&y.to_be_bytes());
+                body.push(*radius);
+            }
+            Message::Sight { radius } => body.extend_from_slice(&[SIGHT, *radius]),
+            Message::Enemies { enemies } => {
+                body.extend_from_slice(&[ENEMIES, enemies.len() as u8]);
+                for (behavior, x, y) in enemies {
+                    body.push(behavior_code(behavior));
+                    body.extend_from_slice(&x.to_be_bytes());
+                    body.extend_from_slice(&y.to_be_bytes());
+                }
+            }
+            Message::GameOver { results } => {
+                body.extend_from_slice(&[GAME_OVER, results.len() as u8]);
+                for placing in results {
+                    body.extend_from_slice(&[placing.player, placing.place]);
+                    match placing.finish {
+                        Finish::Escaped { time_ms } => {
+                            body.push(FINISH_ESCAPED);
+                            body.extend_from_slice(&time_ms.to_be_bytes());
+                        }
+                        Finish::Bombed => body.push(FINISH_BOMBED),
+                        Finish::Left => body.push(FINISH_LEFT),
+                    }
+                }
+            }
+            Message::Rematch => body.push(REMATCH),
+            Message::RematchVote { player } => body.extend_from_slice(&[REMATCH_VOTE, *player]),
+            Message::Chat { text } => {
+                body.push(CHAT);
+                body.extend_from_slice(text.as_bytes());
+            }
+            Message::Ping { nonce } | Message::Pong { nonce } => {
+                body.push(if matches!(self, Message::Ping { .. }) { PING } else { PONG });
+                body.extend_from_slice(&nonce.to_be_bytes());
+            }
+        }
+        let mut frame = (body.len() as u32).to_be_bytes().to_vec();
+        frame.extend(body);
+        frame
+    }
+
+    // 길이 앞머리를 뗀 나머지(종류 + 본문)를 해석한다
+    pub fn decode(body: &[u8]) -> io::Result<Message> {
+        let (&kind, rest) = body.split_first().ok_or_else(|| invalid("empty message"))?;
+        let mut r = Reader { buf: rest };
+        let message = match kind {
+            HELLO => {
+                if r.take(4)? != MAGIC {
+                    return Err(invalid("not a maze game connection"));
+                }
+                Message::Hello { version: r.u16()? }
+            }
+            WELCOME => Message::Welcome { player: r.u8()?, token: r.u64()? },
+            REJOIN => Message::Rejoin { token: r.u64()? },
+            PLAYER_AWAY => Message::PlayerAway { player: r.u8()? },
+            PLAYER_BACK => Message::PlayerBack { player: r.u8()? },
+            PLAYER_LEFT => Message::PlayerLeft { player: r.u8()? },
+            LIST_ROOMS => Message::ListRooms,
+            CREATE_ROOM => {
+                let room = r.name()?;
+                let players = r.u8()?;
+                let seed = r.u64()?;
+                let width = r.u16()?;
+                let height = r.u16()?;
+                let bombs = r.u16()?;
+                let doors = r.u8()?;
+                let count = r.u8()?;
+                let mut enemies = Vec::new();
+                for _ in 0..count {
+                    enemies.push(r.behavior()?);
+                }
+                Message::CreateRoom { room, players, seed, width, height, bombs, doors, enemies, algorithm: r.string()? }
+            }
+            JOIN_ROOM => Message::JoinRoom { room: r.name()? },
+            WATCH => Message::Watch { room: r.name()? },
+            LEAVE_ROOM => Message::LeaveRoom,
+            ROOM_LIST => {
+                let count = r.u16()?;
+                let mut rooms = Vec::new();
+                for _ in 0..count {
+                    rooms.push(r.room()?);
+                }
+                Message::RoomList { rooms }
+            }
+            REFUSED => Message::Refused { reason: r.string()? },
+            MAZE_DATA => {
+                let width = r.u16()? as usize;
+                let height = r.u16()? as usize;
+                if width == 0 || height == 0 {
+                    return Err(invalid("empty maze"));
+                }
+                let cells = r.take(width * height)?;
+                let map = cells.chunks(height).map(|column| column.iter().map(|&b| b as char).collect()).collect();
+                Message::MazeData { map }
+            }
+            MOVE => {
+                let dir = match r.u8()? {
+                    0 => Direction::Up,
+                    1 => Direction::Down,
+                    2 => Direction::Left,
+                    3 => Direction::Right,
+                    other => return Err(invalid(format!("unknown direction {}", other))),
+                };
+                Message::Move { dir }
+            }
+            PLAYER_MOVE => Message::PlayerMove { player: r.u8()?, x: r.u16()?, y: r.u16()? },
+            BOMBED => Message::Bombed { player: r.u8()?, x: r.u16()?, y: r.u16()? },
+            PLAYER_FINISHED => Message::PlayerFinished { player: r.u8()?, time_ms: r.u32()? },
+            READY => Message::Ready,
+            COUNTDOWN => Message::Countdown { seconds: r.u8()? },
+            GO => Message::Go,
+            FUSE => Message::Fuse { ms: r.u32()?, radius: r.u8()? },
+            EXPLODED => Message::Exploded { x: r.u16()?, y: r.u16()?, radius: r.u8()? },
+            SIGHT => Message::Sight { radius: r.u8()? },
+            ENEMIES => {
+                let count = r.u8()?;
+                let mut enemies = Vec::new();
+                for _ in 0..count {
+                    enemies.push((r.behavior()?, r.u16()?, r.u16()?));
+                }
+                Message::Enemies { enemies }
+            }
+            GAME_OVER => {
+                let count = r.u8()?;
+                let mut results = Vec::new();
+                for _ in 0..count {
+                    results.push(r.placing()?);
+                }
+                Message::GameOver { results }
+            }
+            REMATCH => Message::Rematch,
+            REMATCH_VOTE => Message::RematchVote { player: r.u8()? },
+            CHAT => Message::Chat { text: r.string()? },
+            PING => Message::Ping { nonce: r.u32()? },
+            PONG => Message::Pong { nonce: r.u32()? },
+            _ => return Err(invalid(format!("unknown message type {}", kind))),
+        };
+        r.finish()?;
+        Ok(message)
+    }
+}
+
+pub fn write_message(w: &mut impl Write, message: &Message) -> io::Result<()> {
+    w.write_all(&message.encode())
+}
+
+pub fn read_message(r: &mut impl Read) -> io::Result<Message> {
+    let mut len = [0u8; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME {
+        return Err(invalid(format!("message of {} bytes is too large", len)));
+    }
+    let mut body = vec![0u8; len];
+    r.read_exact(&mut body)?;
+    Message::decode(&body)
+}
+
+// 내 Hello 를 보내고 상대 Hello 를 받는다. 버전이 다르면 에러
+pub fn handshake(stream: &mut (impl Read + Write)) -> io::Result<()> {
+    write_message(stream, &Message::Hello { version: VERSION })?;
+    match read_message(stream)? {
+        Message::Hello { version } if version == VERSION => Ok(()),
+        Message::Hello { version } => Err(invalid(format!("protocol version {} does not match ours ({})", version, VERSION))),
+        other => Err(invalid(format!("expected Hello, got {:?}", other))),
+    }
+}
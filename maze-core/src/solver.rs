@@ -0,0 +1,307 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+use crate::{Direction, BOMB, DOORS, KEYS, WALL};
+
+type Pos = (usize, usize);
+
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Right, Direction::Down, Direction::Left];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    Bfs,
+    AStar,
+    WallFollower,
+    Tremaux,
+    DeadEndFilling,
+}
+
+pub const STRATEGIES: [Strategy; 5] = [
+    Strategy::Bfs,
+    Strategy::AStar,
+    Strategy::WallFollower,
+    Strategy::Tremaux,
+    Strategy::DeadEndFilling,
+];
+
+// start 에서 goal 까지 지나는 칸 목록 (양 끝 포함). 벽과 폭탄, 잠긴 문은 지나갈 수 없다.
+// Bfs, AStar, DeadEndFilling 은 최단 경로를, 나머지는 그 전략으로 찾은 경로를 돌려준다
+pub fn solve(map: &[Vec<char>], start: Pos, goal: Pos, strategy: Strategy) -> Option<Vec<Pos>> {
+    match strategy {
+        Strategy::Bfs => bfs(map, start, goal),
+        Strategy::AStar => astar(map, start, goal),
+        Strategy::WallFollower => wall_follower(map, start, goal),
+        Strategy::Tremaux => tremaux(map, start, goal),
+        Strategy::DeadEndFilling => dead_end_filling(map, start, goal),
+    }
+}
+
+pub fn is_open(map: &[Vec<char>], pos: Pos) -> bool {
+    let cell = map[pos.0][pos.1];
+    cell != WALL && cell != BOMB && door_index(cell).is_none()
+}
+
+pub fn key_index(cell: char) -> Option<usize> {
+    KEYS.iter().position(|&key| key == cell)
+}
+
+pub fn door_index(cell: char) -> Option<usize> {
+    DOORS.iter().position(|&door| door == cell)
+}
+
+// 가진 열쇠를 비트로. i 번 비트가 KEYS[i]
+fn key_mask(held: &[char]) -> u8 {
+    held.iter().filter_map(|&key| key_index(key)).fold(0, |mask, i| mask | 1 << i)
+}
+
+// keys 에 짝이 있는 문은 열린 것으로 본다
+fn is_unlocked(map: &[Vec<char>], pos: Pos, keys: u8) -> bool {
+    is_open(map, pos) || door_index(map[pos.0][pos.1]).is_some_and(|i| keys & 1 << i != 0)
+}
+
+fn offset(map: &[Vec<char>], pos: Pos, dir: Direction) -> Option<Pos> {
+    let (dx, dy) = dir.offset();
+    let (x, y) = (pos.0 as i32 + dx, pos.1 as i32 + dy);
+    if x < 0 || y < 0 || x >= map.len() as i32 || y >= map[0].len() as i32 {
+        return None;
+    }
+    Some((x as usize, y as usize))
+}
+
+fn step(map: &[Vec<char>], pos: Pos, dir: Direction) -> Option<Pos> {
+    offset(map, pos, dir).filter(|&next| is_open(map, next))
+}
+
+fn neighbors(map: &[Vec<char>], pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+    DIRECTIONS.iter().filter_map(move |&dir| step(map, pos, dir))
+}
+
+fn walk_back<T: Copy + Eq + Hash>(came_from: &HashMap<T, T>, start: T, goal: T) -> Vec<T> {
+    let mut path = vec![goal];
+    let mut cur = goal;
+    while cur != start {
+        cur = came_from[&cur];
+        path.push(cur);
+    }
+    path.reverse();
+    path
+}
+
+pub fn bfs(map: &[Vec<char>], start: Pos, goal: Pos) -> Option<Vec<Pos>> {
+    let mut came_from = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    came_from.insert(start, start);
+    while let Some(cur) = queue.pop_front() {
+        if cur == goal {
+            return Some(walk_back(&came_from, start, goal));
+        }
+        for next in neighbors(map, cur) {
+            if let Entry::Vacant(entry) = came_from.entry(next) {
+                entry.insert(cur);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+// bfs 와 같지만 start 에서 limit 걸음 안에서만 찾는다. 그보다 멀면 None
+pub fn bfs_within(map: &[Vec<char>], start: Pos, goal: Pos, limit: usize) -> Option<Vec<Pos>> {
+    let mut came_from = HashMap::from([(start, start)]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((cur, steps)) = queue.pop_front() {
+        if cur == goal {
+            return Some(walk_back(&came_from, start, goal));
+        }
+        if steps == limit {
+            continue;
+        }
+        for next in neighbors(map, cur) {
+            if let Entry::Vacant(entry) = came_from.entry(next) {
+                entry.insert(cur);
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+    None
+}
+
+// 열쇠를 주우며 가는 가장 짧은 길. (칸, 가진 열쇠) 를 한 상태로 넓이 우선 탐색하므로
+// 문 너머의 열쇠를 먼저 가지러 가는 것처럼 순서가 정해진 퍼즐도 푼다
+pub fn bfs_with_keys(map: &[Vec<char>], start: Pos, goal: Pos, held: &[char]) -> Option<Vec<Pos>> {
+    let first = (start, key_mask(held));
+    let mut came_from = HashMap::from([(first, first)]);
+    let mut queue = VecDeque::from([first]);
+    while let Some(cur) = queue.pop_front() {
+        let (pos, keys) = cur;
+        if pos == goal {
+            return Some(walk_back(&came_from, first, cur).into_iter().map(|(pos, _)| pos).collect());
+        }
+        for &dir in &DIRECTIONS {
+            let next = match offset(map, pos, dir) {
+                Some(next) if is_unlocked(map, next, keys) => next,
+                _ => continue,
+            };
+            let keys = key_index(map[next.0][next.1]).map_or(keys, |i| keys | 1 << i);
+            if let Entry::Vacant(entry) = came_from.entry((next, keys)) {
+                entry.insert(cur);
+                queue.push_back((next, keys));
+            }
+        }
+    }
+    None
+}
+
+// held 만 가지고 (바닥의 열쇠는 줍지 않고) start 에서 닿는 칸. reachable[x][y] 로 본다
+pub fn reachable(map: &[Vec<char>], start: Pos, held: &[char]) -> Vec<Vec<bool>> {
+    let keys = key_mask(held);
+    let mut seen = vec![vec![false; map[0].len()]; map.len()];
+    seen[start.0][start.1] = true;
+    let mut queue = VecDeque::from([start]);
+    while let Some(pos) = queue.pop_front() {
+        for &dir in &DIRECTIONS {
+            if let Some(next) = offset(map, pos, dir).filter(|&next| is_unlocked(map, next, keys)) {
+                if !seen[next.0][next.1] {
+                    seen[next.0][next.1] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    seen
+}
+
+// start 에서 각 칸까지 걸음 수. 닿지 못하면 None
+pub fn distances(map: &[Vec<char>], start: Pos) -> Vec<Vec<Option<usize>>> {
+    let mut dist = vec![vec![None; map[0].len()]; map.len()];
+    dist[start.0][start.1] = Some(0);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((cur, d)) = queue.pop_front() {
+        for next in neighbors(map, cur) {
+            if dist[next.0][next.1].is_none() {
+                dist[next.0][next.1] = Some(d + 1);
+                queue.push_back((next, d + 1));
+            }
+        }
+    }
+    dist
+}
+
+// 맨해튼 거리를 휴리스틱으로 쓰는 A*
+pub fn astar(map: &[Vec<char>], start: Pos, goal: Pos) -> Option<Vec<Pos>> {
+    let manhattan = |p: Pos| p.0.abs_diff(goal.0) + p.1.abs_diff(goal.1);
+    let mut came_from = HashMap::new();
+    let mut cost = HashMap::from([(start, 0)]);
+    let mut open = BinaryHeap::from([Reverse((manhattan(start), 0, start))]);
+    while let Some(Reverse((_, g, cur))) = open.pop() {
+        if cur == goal {
+            return Some(walk_back(&came_from, start, goal));
+        }
+        if g > cost[&cur] {
+            continue;
+        }
+        for next in neighbors(map, cur) {
+            if cost.get(&next).is_none_or(|&c| g + 1 < c) {
+                cost.insert(next, g + 1);
+                came_from.insert(next, cur);
+                open.push(Reverse((g + 1 + manhattan(next), g + 1, next)));
+            }
+        }
+    }
+    None
+}
+
+// 왼손 법칙. 시작 칸에 같은 방향으로 돌아오면 출구가 벽으로 이어져 있지 않은 것이다.
+// 걸은 길에서 되돌아온 구간은 지워서 돌려준다
+pub fn wall_follower(map: &[Vec<char>], start: Pos, goal: Pos) -> Option<Vec<Pos>> {
+    let mut heading = 0;
+    let mut cur = start;
+    let mut path = vec![start];
+    let limit = 4 * map.len() * map[0].len() + 4;
+    for _ in 0..limit {
+        if cur == goal {
+            return Some(path);
+        }
+        // 왼쪽, 앞, 오른쪽, 뒤 순서로 시도
+        let (dir, next) = [3, 0, 1, 2].iter().find_map(|&by| {
+            let dir = (heading + by) % 4;
+            step(map, cur, DIRECTIONS[dir]).map(|next| (dir, next))
+        })?;
+        heading = dir;
+        cur = next;
+        match path.iter().position(|&p| p == cur) {
+            Some(index) => path.truncate(index + 1),
+            None => path.push(cur),
+        }
+    }
+    None
+}
+
+// 트레모 알고리즘: 지나간 통로에 표시를 남기고, 두 번 표시된 통로로는 다시 가지 않는다.
+// 한 번만 표시된 통로들이 start 와 goal 을 잇는 길이 된다
+pub fn tremaux(map: &[Vec<char>], start: Pos, goal: Pos) -> Option<Vec<Pos>> {
+    let edge = |a: Pos, b: Pos| if a < b { (a, b) } else { (b, a) };
+    let mut marks: HashMap<(Pos, Pos), u8> = HashMap::new();
+    let mut seen = vec![vec![false; map[0].len()]; map.len()];
+    let mut cur = start;
+    let mut prev: Option<Pos> = None;
+
+    while cur != goal {
+        let exits: Vec<Pos> = neighbors(map, cur).collect();
+        let count = |next: Pos| marks.get(&edge(cur, next)).copied().unwrap_or(0);
+        let was_seen = seen[cur.0][cur.1];
+        seen[cur.0][cur.1] = true;
+
+        let next = match prev {
+            // 새 통로로 이미 와 본 칸에 도착했으면 온 길로 되돌아간다
+            Some(p) if was_seen && count(p) == 1 => Some(p),
+            _ => exits.iter().copied().find(|&n| Some(n) != prev && count(n) == 0)
+                .or_else(|| prev.filter(|&p| count(p) == 1))
+                .or_else(|| exits.iter().copied().find(|&n| count(n) == 1)),
+        }?;
+        *marks.entry(edge(cur, next)).or_insert(0) += 1;
+        prev = Some(cur);
+        cur = next;
+    }
+
+    // 한 번만 표시된 통로만 따라 start 에서 goal 로
+    let mut came_from = HashMap::from([(start, start)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(p) = queue.pop_front() {
+        if p == goal {
+            return Some(walk_back(&came_from, start, goal));
+        }
+        for n in neighbors(map, p) {
+            if marks.get(&edge(p, n)) == Some(&1) && !came_from.contains_key(&n) {
+                came_from.insert(n, p);
+                queue.push_back(n);
+            }
+        }
+    }
+    None
+}
+
+// 막다른 칸을 더 이상 없을 때까지 메운 뒤 남은 칸에서 길을 찾는다
+pub fn dead_end_filling(map: &[Vec<char>], start: Pos, goal: Pos) -> Option<Vec<Pos>> {
+    let mut filled: Vec<Vec<char>> = map.to_vec();
+    let open_count = |filled: &[Vec<char>], p: Pos| neighbors(filled, p).count();
+    let mut queue: VecDeque<Pos> = VecDeque::new();
+    for x in 0..map.len() {
+        for y in 0..map[0].len() {
+            if is_open(map, (x, y)) && open_count(map, (x, y)) <= 1 {
+                queue.push_back((x, y));
+            }
+        }
+    }
+    while let Some(p) = queue.pop_front() {
+        if p == start || p == goal || !is_open(&filled, p) || open_count(&filled, p) > 1 {
+            continue;
+        }
+        let rest: Vec<Pos> = neighbors(&filled, p).collect();
+        filled[p.0][p.1] = WALL;
+        queue.extend(rest);
+    }
+    bfs(&filled, start, goal)
+}
@@ -0,0 +1,71 @@
+use maze_core::enemy::{self, Enemy};
+use maze_core::{generator, Behavior, Difficulty, EnemyRules, GameState, WALL};
+
+fn corridor(len: usize) -> Vec<Vec<char>> {
+    vec![vec![' '; 1]; len]
+}
+
+#[test]
+fn chasers_run_down_a_player_who_stands_still() {
+    let map = corridor(10);
+    let mut chaser = Enemy::new(Behavior::Chase, (9, 0));
+    for _ in 0..9 {
+        chaser.step(&map, &[(0, 0)], (9, 0), &EnemyRules::default());
+    }
+    assert!(enemy::caught(&[chaser], (0, 0)));
+}
+
+#[test]
+fn chasers_close_in_on_players_beyond_their_search_range() {
+    let map = corridor(500);
+    let mut chaser = Enemy::new(Behavior::Chase, (499, 0));
+    for _ in 0..50 {
+        chaser.step(&map, &[(0, 0)], (499, 0), &EnemyRules::default());
+    }
+    assert_eq!(chaser.pos, (449, 0));
+}
+
+#[test]
+fn ambushers_wait_until_they_see_someone() {
+    // 가운데 벽으로 둘로 나뉜 방. 플레이어는 벽 너머에 있다
+    let mut map = vec![vec![' '; 5]; 9];
+    map[4] = vec![WALL; 5];
+    map[4][4] = ' ';
+    let rules = EnemyRules::default();
+    let mut ambusher = Enemy::new(Behavior::Ambush, (6, 0));
+    for _ in 0..5 {
+        ambusher.step(&map, &[(2, 0)], (0, 4), &rules);
+    }
+    assert_eq!(ambusher.pos, (6, 0));
+
+    let mut patrol = Enemy::new(Behavior::Patrol, (6, 0));
+    patrol.step(&map, &[(6, 3)], (0, 4), &rules);
+    assert_eq!(patrol.pos, (6, 1), "patrols chase whoever they see");
+}
+
+#[test]
+fn same_seed_and_moves_give_the_same_enemies() {
+    let generator = generator::by_name("prim").unwrap();
+    let game = GameState::generate(3, generator.as_ref(), 30, 30, 2, 1);
+    let behaviors = Difficulty::Hard.enemies();
+    let run = || {
+        let mut enemies = enemy::spawn(&game, behaviors, 3);
+        let path = game.solve(maze_core::Strategy::Bfs).unwrap();
+        let mut trace = Vec::new();
+        for &player in &path {
+            for enemy in &mut enemies {
+                enemy.step(&game.map, &[player], game.exit, &EnemyRules::default());
+            }
+            trace.push(enemies.iter().map(|enemy| enemy.pos).collect::<Vec<_>>());
+        }
+        trace
+    };
+    let first = run();
+    assert_eq!(first[0].len(), behaviors.len());
+    assert_eq!(first, run());
+    let start = enemy::spawn(&game, behaviors, 3);
+    for enemy in &start {
+        assert_ne!(enemy.pos, game.player);
+    }
+    assert_ne!(start, enemy::spawn(&game, behaviors, 4), "another seed places them elsewhere");
+}
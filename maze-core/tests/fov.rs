@@ -0,0 +1,36 @@
+use maze_core::fov;
+use maze_core::WALL;
+
+fn open_room(size: usize) -> Vec<Vec<char>> {
+    vec![vec![' '; size]; size]
+}
+
+#[test]
+fn open_room_is_lit_within_radius() {
+    let map = open_room(11);
+    let seen = fov::visible(&map, (5, 5), 3);
+    for (x, column) in seen.iter().enumerate() {
+        for (y, &lit) in column.iter().enumerate() {
+            let (dx, dy) = (x.abs_diff(5), y.abs_diff(5));
+            assert_eq!(lit, dx * dx + dy * dy <= 9, "{:?}", (x, y));
+        }
+    }
+}
+
+#[test]
+fn walls_cast_shadows_but_are_seen() {
+    // (5, 5) 에서 오른쪽으로 두 칸 떨어진 세로 벽
+    let mut map = open_room(11);
+    for cell in &mut map[7][3..=7] {
+        *cell = WALL;
+    }
+    let seen = fov::visible(&map, (5, 5), 5);
+    assert!(seen[7][5], "the wall itself is visible");
+    assert!(seen[6][5]);
+    assert!(!seen[8][5] && !seen[9][5] && !seen[10][5], "cells behind the wall stay dark");
+    assert!(seen[3][5] && seen[5][1], "the other side is open");
+
+    // 트인 곳이어도 원 밖은 보이지 않는다
+    let seen = fov::visible(&open_room(11), (0, 0), 2);
+    assert!(seen[2][0] && seen[1][1] && !seen[2][2]);
+}
@@ -0,0 +1,50 @@
+use maze_core::{generator, GameState, Outcome, BOMB, EXIT, PLAYER, WALL};
+
+// 7x7 벽 안에 폭탄 하나. 플레이어는 폭탄에서 (2, 0) 떨어진 곳
+fn walled(player: (usize, usize)) -> GameState {
+    let mut map = vec![vec![WALL; 7]; 7];
+    map[3][3] = BOMB;
+    map[player.0][player.1] = PLAYER;
+    map[6][6] = EXIT;
+    GameState::from_map(map)
+}
+
+#[test]
+fn explosions_clear_walls_in_range_and_catch_players() {
+    let mut game = walled((5, 3));
+    game.explode((3, 3), 1);
+    for x in 0..7usize {
+        for y in 0..7usize {
+            let inside = x.abs_diff(3) <= 1 && y.abs_diff(3) <= 1;
+            assert_eq!(game.map[x][y] == ' ', inside, "{:?}", (x, y));
+        }
+    }
+    assert_eq!(game.outcome, Outcome::Playing);
+
+    let mut game = walled((5, 3));
+    game.explode((3, 3), 3);
+    assert_eq!(game.outcome, Outcome::Bombed);
+    assert_eq!(game.map[6][6], EXIT);
+}
+
+#[test]
+fn the_same_seed_builds_the_same_maze() {
+    for name in generator::ALGORITHMS {
+        let generator = generator::by_name(name).unwrap();
+        let first = GameState::generate(42, generator.as_ref(), 21, 15, 6, 2);
+        let second = GameState::generate(42, generator.as_ref(), 21, 15, 6, 2);
+        assert_eq!(first.map, second.map, "{}", name);
+        assert_eq!(first.player, second.player, "{}", name);
+        assert_eq!(first.bombs, second.bombs, "{}", name);
+        assert_eq!(first.exit, second.exit, "{}", name);
+        assert_eq!(first.seed, Some(42), "{}", name);
+    }
+}
+
+#[test]
+fn another_seed_builds_another_maze() {
+    let generator = generator::by_name("dfs").unwrap();
+    let first = GameState::generate(1, generator.as_ref(), 21, 15, 6, 2);
+    let second = GameState::generate(2, generator.as_ref(), 21, 15, 6, 2);
+    assert_ne!(first.map, second.map);
+}
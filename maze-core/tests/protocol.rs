@@ -0,0 +1,144 @@
+use std::io::Cursor;
+
+use maze_core::protocol::{self, Beacon, Finish, Message, Placing, RoomInfo, MAX_FRAME, MAX_NAME, VERSION};
+use maze_core::{generator, Behavior, Direction, GameState};
+
+fn round_trip(message: Message) {
+    let frame = message.encode();
+    assert_eq!(u32::from_be_bytes(frame[..4].try_into().unwrap()) as usize, frame.len() - 4);
+    assert_eq!(Message::decode(&frame[4..]).unwrap(), message);
+    assert_eq!(protocol::read_message(&mut Cursor::new(frame)).unwrap(), message);
+}
+
+#[test]
+fn every_message_round_trips() {
+    let game = GameState::generate(7, generator::by_name("prim").unwrap().as_ref(), 31, 20, 4, 2);
+    round_trip(Message::Hello { version: VERSION });
+    round_trip(Message::MazeData { map: game.map });
+    round_trip(Message::Welcome { player: 1, token: 0x0123_4567_89ab_cdef });
+    round_trip(Message::Rejoin { token: 42 });
+    round_trip(Message::PlayerAway { player: 2 });
+    round_trip(Message::PlayerBack { player: 2 });
+    round_trip(Message::PlayerLeft { player: 7 });
+    round_trip(Message::ListRooms);
+    round_trip(Message::CreateRoom { room: "방 1".to_string(), players: 8, seed: u64::MAX, algorithm: "hunt-and-kill".to_string(), width: 41, height: 23, bombs: 6, doors: 3, enemies: vec![Behavior::Ambush, Behavior::Patrol] });
+    round_trip(Message::JoinRoom { room: "friday".to_string() });
+    round_trip(Message::Watch { room: "friday".to_string() });
+    round_trip(Message::LeaveRoom);
+    round_trip(Message::RoomList { rooms: Vec::new() });
+    round_trip(Message::RoomList {
+        rooms: vec![
+            RoomInfo { name: "a".to_string(), players: 1, capacity: 2, playing: false, width: 30, height: 30 },
+            RoomInfo { name: String::new(), players: 2, capacity: 8, playing: true, width: 500, height: 5 },
+        ],
+    });
+    round_trip(Message::Refused { reason: "room is full".to_string() });
+    for dir in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+        round_trip(Message::Move { dir });
+    }
+    round_trip(Message::PlayerMove { player: 0, x: 3, y: 499 });
+    round_trip(Message::PlayerFinished { player: 7, time_ms: 61_234 });
+    round_trip(Message::Bombed { player: 1, x: 0, y: 0 });
+    round_trip(Message::Ready);
+    round_trip(Message::Countdown { seconds: 3 });
+    round_trip(Message::Go);
+    round_trip(Message::Fuse { ms: 30_000, radius: 2 });
+    round_trip(Message::Sight { radius: 5 });
+    round_trip(Message::Enemies { enemies: vec![(Behavior::Chase, 3, 40), (Behavior::Patrol, 0, 0)] });
+    round_trip(Message::Exploded { x: 12, y: 499, radius: 3 });
+    round_trip(Message::GameOver { results: protocol::placings(&[Finish::Bombed, Finish::Escaped { time_ms: 12_345 }, Finish::Left]) });
+    round_trip(Message::GameOver { results: Vec::new() });
+    round_trip(Message::Rematch);
+    round_trip(Message::RematchVote { player: 3 });
+    round_trip(Message::Chat { text: "안녕 gg".to_string() });
+    round_trip(Message::Chat { text: String::new() });
+    round_trip(Message::Ping { nonce: u32::MAX });
+    round_trip(Message::Pong { nonce: 1 });
+}
+
+#[test]
+fn messages_are_read_back_to_back() {
+    let messages = [Message::PlayerMove { player: 0, x: 1, y: 2 }, Message::Ping { nonce: 9 }, Message::PlayerMove { player: 1, x: 1, y: 3 }];
+    let stream: Vec<u8> = messages.iter().flat_map(Message::encode).collect();
+    let mut cursor = Cursor::new(stream);
+    for message in &messages {
+        assert_eq!(&protocol::read_message(&mut cursor).unwrap(), message);
+    }
+    assert!(protocol::read_message(&mut cursor).is_err());
+}
+
+#[test]
+fn marathon_mazes_fit_in_one_frame() {
+    let message = Message::MazeData { map: vec![vec!['#'; 2000]; 2000] };
+    let mut cursor = Cursor::new(message.encode());
+    assert_eq!(protocol::read_message(&mut cursor).unwrap(), message);
+}
+
+#[test]
+fn malformed_frames_are_rejected() {
+    // 알 수 없는 종류, 잘린 본문, 남는 바이트, 잘못된 magic
+    assert!(Message::decode(&[200]).is_err());
+    assert!(Message::decode(&[]).is_err());
+    assert!(Message::decode(&[2, 0, 0, 1, 0]).is_err());
+    assert!(Message::decode(&[8, 4]).is_err());
+    assert!(Message::decode(&[6, 0, 0, 0, 1, 0]).is_err());
+    assert!(Message::decode(&[0, b'N', b'O', b'P', b'E', 0, 1]).is_err());
+    let too_big = ((MAX_FRAME + 1) as u32).to_be_bytes();
+    assert!(protocol::read_message(&mut Cursor::new(too_big)).is_err());
+}
+
+#[test]
+fn handshake_rejects_other_versions() {
+    // 보낸 Hello 는 버리고, 미리 넣어 둔 상대 Hello 를 읽는다
+    struct Peer(Cursor<Vec<u8>>);
+    impl std::io::Read for Peer {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+    impl std::io::Write for Peer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let hello = |version| Peer(Cursor::new(Message::Hello { version }.encode()));
+    assert!(protocol::handshake(&mut hello(VERSION)).is_ok());
+    assert!(protocol::handshake(&mut hello(VERSION + 1)).is_err());
+    assert!(protocol::handshake(&mut Peer(Cursor::new(Message::Ping { nonce: 0 }.encode()))).is_err());
+}
+
+#[test]
+fn long_room_names_are_cut() {
+    let room = "x".repeat(MAX_NAME + 10);
+    let frame = Message::JoinRoom { room }.encode();
+    assert_eq!(Message::decode(&frame[4..]).unwrap(), Message::JoinRoom { room: "x".repeat(MAX_NAME) });
+}
+
+#[test]
+fn beacons_round_trip_and_keep_to_one_datagram() {
+    let room = |i: usize| RoomInfo { name: format!("room {}", i), players: 1, capacity: 4, playing: i > 0, width: 30, height: 20 };
+    let beacon = Beacon { version: VERSION, name: "lab".to_string(), port: 8088, players: 3, rooms: vec![room(0), room(1)] };
+    assert_eq!(Beacon::decode(&beacon.encode()).unwrap(), beacon);
+
+    let crowded = Beacon { rooms: (0..100).map(room).collect(), ..beacon };
+    let datagram = crowded.encode();
+    assert!(datagram.len() < 1400);
+    assert_eq!(Beacon::decode(&datagram).unwrap().rooms, crowded.rooms[..16]);
+    assert!(Beacon::decode(b"MAZE\x00\x05").is_err());
+}
+
+#[test]
+fn placings_share_ties_and_skip_places() {
+    let finishes = [
+        Finish::Escaped { time_ms: 9_000 },
+        Finish::Left,
+        Finish::Escaped { time_ms: 7_500 },
+        Finish::Bombed,
+        Finish::Escaped { time_ms: 7_500 },
+    ];
+    let places: Vec<(u8, u8)> = protocol::placings(&finishes).iter().map(|placing: &Placing| (placing.player, placing.place)).collect();
+    assert_eq!(places, vec![(2, 1), (4, 1), (0, 3), (3, 4), (1, 5)]);
+}
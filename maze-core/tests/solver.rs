@@ -0,0 +1,107 @@
+use maze_core::solver::{self, STRATEGIES};
+use maze_core::{generator, Difficulty, Direction, GameState, Outcome, Strategy, WALL, BOMB, DOORS, KEYS};
+
+fn assert_valid_path(game: &GameState, path: &[(usize, usize)]) {
+    assert_eq!(path.first(), Some(&game.player));
+    assert_eq!(path.last(), Some(&game.exit));
+    for pair in path.windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        assert_eq!(a.0.abs_diff(b.0) + a.1.abs_diff(b.1), 1, "{:?} -> {:?} is not a single step", a, b);
+    }
+    for &(x, y) in path {
+        assert!(game.map[x][y] != WALL && game.map[x][y] != BOMB, "path goes through {:?}", (x, y));
+    }
+}
+
+#[test]
+fn every_generated_maze_is_solvable_from_the_exit_position() {
+    for name in generator::ALGORITHMS {
+        let generator = generator::by_name(name).unwrap();
+        for &(width, height) in &[(15, 15), (30, 30), (41, 23)] {
+            for seed in 0..10 {
+                let bombs = Difficulty::Hard.bombs(width, height);
+                let game = GameState::generate(seed, generator.as_ref(), width, height, bombs, 0);
+                assert!(!game.bombs.is_empty(), "{} seed {} has no bombs", name, seed);
+                for &(x, y) in &game.bombs {
+                    assert_eq!(game.map[x][y], BOMB);
+                }
+                let shortest = game.solve(Strategy::Bfs).unwrap_or_else(|| panic!("{} seed {} unsolvable", name, seed));
+                assert_valid_path(&game, &shortest);
+
+                for strategy in STRATEGIES {
+                    let path = game.solve(strategy).unwrap_or_else(|| panic!("{:?} failed on {} seed {}", strategy, name, seed));
+                    assert_valid_path(&game, &path);
+                    if matches!(strategy, Strategy::AStar | Strategy::DeadEndFilling) {
+                        assert_eq!(path.len(), shortest.len(), "{:?} is not shortest on {} seed {}", strategy, name, seed);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn direction(from: (usize, usize), to: (usize, usize)) -> Direction {
+    match (to.0 as i32 - from.0 as i32, to.1 as i32 - from.1 as i32) {
+        (0, -1) => Direction::Up,
+        (0, 1) => Direction::Down,
+        (-1, 0) => Direction::Left,
+        _ => Direction::Right,
+    }
+}
+
+#[test]
+fn locked_mazes_need_every_key_in_order() {
+    for name in generator::ALGORITHMS {
+        let generator = generator::by_name(name).unwrap();
+        for seed in 0..10 {
+            let mut game = GameState::generate(seed, generator.as_ref(), 30, 30, 4, KEYS.len());
+            // 출구로 가는 길이 짧으면 문을 덜 놓는다. 놓인 문은 앞에서부터 차례로, 열쇠와 짝을 이룬다
+            let doors = DOORS.iter().take_while(|&&door| game.map.iter().flatten().any(|&c| c == door)).count();
+            for i in 0..KEYS.len() {
+                let placed = |cell: char| game.map.iter().flatten().filter(|&&c| c == cell).count();
+                let expected = usize::from(i < doors);
+                assert_eq!((placed(KEYS[i]), placed(DOORS[i])), (expected, expected), "{} seed {} pair {}", name, seed, i);
+            }
+            if doors > 0 {
+                // 문을 벽처럼 막으면 나갈 수 없다
+                assert_eq!(solver::bfs(&game.map, game.player, game.exit), None, "{} seed {} needs no key", name, seed);
+            }
+
+            let path = game.solve(Strategy::Bfs).unwrap_or_else(|| panic!("{} seed {} unsolvable", name, seed));
+            for pair in path.windows(2) {
+                assert!(game.step(direction(pair[0], pair[1])), "{} seed {} blocked at {:?}", name, seed, pair[1]);
+            }
+            assert_eq!(game.outcome, Outcome::Escaped);
+            assert_eq!(game.keys, KEYS[..doors], "{} seed {} picked keys out of order", name, seed);
+        }
+    }
+}
+
+#[test]
+fn doors_stay_shut_without_their_key() {
+    let map: Vec<Vec<char>> = ["PA bE", "  a  "].iter().map(|row| row.chars().collect()).collect();
+    let mut game = GameState::from_map(map);
+    assert!(!game.step(Direction::Down));
+    for dir in [Direction::Right, Direction::Down, Direction::Down, Direction::Up, Direction::Up, Direction::Left] {
+        assert!(game.step(dir));
+    }
+    assert_eq!(game.keys, ['a']);
+    assert!(game.step(Direction::Down));
+    assert_eq!(game.map[0][1], ' ', "opened door stays open");
+    assert_eq!(solver::bfs_with_keys(&game.map, game.player, game.exit, &game.keys).map(|path| path.len()), Some(4));
+}
+
+#[test]
+fn blocked_exit_has_no_path() {
+    let map: Vec<Vec<char>> = vec!["P #E".chars().collect()];
+    for strategy in STRATEGIES {
+        assert_eq!(solver::solve(&map, (0, 0), (0, 3), strategy), None, "{:?}", strategy);
+    }
+}
+
+#[test]
+fn bounded_search_gives_up_past_its_limit() {
+    let map = vec![vec![' '; 1]; 10];
+    assert_eq!(solver::bfs_within(&map, (0, 0), (9, 0), 9).map(|path| path.len()), Some(10));
+    assert_eq!(solver::bfs_within(&map, (0, 0), (9, 0), 8), None);
+}
@@ -0,0 +1,111 @@
+use std::env;
+use std::fs;
+
+use maze_core::protocol::DEFAULT_PORT;
+use maze_core::{Behavior, BombRules, Difficulty, EnemyRules};
+
+const CONFIG_FILE: &str = "mazegame.cfg";
+
+// mazegame.cfg 예시
+//   # 주석
+//   name = alice
+//   # Multi/Join 이 접속할 서버. 포트를 빼면 기본 포트
+//   server = 192.168.0.10:8088
+//   algorithm = prim
+//   width = 40
+//   height = 25
+//   # easy, normal, hard. bombs, doors, enemies 를 적으면 난이도와 상관없이 그만큼 놓는다
+//   difficulty = normal
+//   bombs = 8
+//   doors = 2
+//   # 적 하나마다 patrol, chase, ambush 중 하나. 비워 두면 적이 없다
+//   enemies = patrol, ambush
+//   # 혼자 할 때 폭탄이 몇 초 뒤에 터지는지와 폭발 범위 (칸). 멀티에서는 서버 설정을 따른다
+//   fuse = 30
+//   blast = 2
+//   # 어두운 미로: 혼자 할 때 볼 수 있는 거리 (칸). 0 이면 미로 전체가 보인다
+//   sight = 5
+//   # 혼자 할 때 적이 한 칸 움직이는 데 걸리는 시간 (밀리초) 과 플레이어를 알아보는 거리 (칸)
+//   enemy_step = 400
+//   enemy_sight = 6
+pub(crate) struct Config {
+    pub(crate) name: String,
+    pub(crate) server: String,
+    pub(crate) algorithm: String,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) difficulty: Difficulty,
+    pub(crate) bombs: Option<usize>,
+    pub(crate) doors: Option<usize>,
+    pub(crate) enemies: Option<Vec<Behavior>>,
+    pub(crate) bomb: BombRules,
+    pub(crate) sight: u8,
+    pub(crate) enemy: EnemyRules,
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let name = env::var("USER").or_else(|_| env::var("USERNAME")).unwrap_or_else(|_| "player".to_string());
+        let server = format!("127.0.0.1:{}", DEFAULT_PORT);
+        let mut config = Config { name, server, algorithm: "dfs".to_string(), width: 30, height: 30, difficulty: Difficulty::Normal, bombs: None, doors: None, enemies: None, bomb: BombRules::default(), sight: 0, enemy: EnemyRules::default() };
+        let text = match fs::read_to_string(CONFIG_FILE) {
+            Ok(text) => text,
+            Err(_) => return config,
+        };
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => continue,
+            };
+            match key {
+                "name" => config.name = value.to_string(),
+                "server" => config.server = value.to_string(),
+                "algorithm" => config.algorithm = value.to_string(),
+                "width" | "height" => match value.parse() {
+                    Ok(n) if key == "width" => config.width = n,
+                    Ok(n) => config.height = n,
+                    Err(_) => println!("{}: '{}' is not a number", CONFIG_FILE, value),
+                },
+                "difficulty" => match Difficulty::by_name(value) {
+                    Some(difficulty) => config.difficulty = difficulty,
+                    None => println!("{}: unknown difficulty '{}'", CONFIG_FILE, value),
+                },
+                "bombs" | "doors" => match value.parse() {
+                    Ok(n) if key == "bombs" => config.bombs = Some(n),
+                    Ok(n) => config.doors = Some(n),
+                    Err(_) => println!("{}: '{}' is not a number", CONFIG_FILE, value),
+                },
+                "enemies" => {
+                    let names = value.split(',').map(str::trim).filter(|name| !name.is_empty());
+                    match names.map(|name| Behavior::by_name(name).ok_or(name)).collect() {
+                        Ok(enemies) => config.enemies = Some(enemies),
+                        Err(name) => println!("{}: unknown enemy behavior '{}'", CONFIG_FILE, name),
+                    }
+                }
+                "fuse" => match value.parse::<u32>() {
+                    Ok(secs) if secs > 0 => config.bomb.fuse_ms = secs.saturating_mul(1000),
+                    _ => println!("{}: '{}' is not a number of seconds", CONFIG_FILE, value),
+                },
+                "blast" => match value.parse() {
+                    Ok(radius) => config.bomb.radius = radius,
+                    Err(_) => println!("{}: '{}' is not a blast radius", CONFIG_FILE, value),
+                },
+                "sight" | "enemy_sight" => match value.parse() {
+                    Ok(sight) if key == "sight" => config.sight = sight,
+                    Ok(sight) => config.enemy.sight = sight,
+                    Err(_) => println!("{}: '{}' is not a sight radius", CONFIG_FILE, value),
+                },
+                "enemy_step" => match value.parse::<u32>() {
+                    Ok(ms) if ms > 0 => config.enemy.step_ms = ms,
+                    _ => println!("{}: '{}' is not a number of milliseconds", CONFIG_FILE, value),
+                },
+                _ => println!("{}: unknown key '{}'", CONFIG_FILE, key),
+            }
+        }
+        config
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use maze_core::protocol::{Beacon, DISCOVERY_PORT, VERSION};
+
+// 이만큼 Beacon 이 안 오면 목록에서 뺀다
+const FORGET_AFTER: Duration = Duration::from_secs(5);
+// 받다가 에러가 나면 이만큼 쉬었다 다시 받는다
+const RETRY_AFTER: Duration = Duration::from_secs(1);
+// 포트를 못 열었으면 (같은 컴퓨터의 다른 클라이언트가 쓰는 중 등) 이만큼마다 다시 열어 본다
+const BIND_AGAIN: Duration = Duration::from_secs(3);
+
+// LAN 에 떠 있는 서버. addr 는 Beacon 을 보낸 IP 에 Beacon 의 TCP 포트를 붙인 것
+pub(crate) struct Found {
+    pub(crate) addr: SocketAddr,
+    pub(crate) beacon: Beacon,
+    seen: Instant,
+}
+
+// DISCOVERY_PORT 로 오는 Beacon 을 뒤쪽 스레드에서 받는다. 포트를 못 열면 그 까닭을 LAN 목록에 보이고 가끔 다시 열어 본다
+pub(crate) struct Discovery {
+    incoming: Option<Receiver<(SocketAddr, Beacon)>>,
+    found: BTreeMap<SocketAddr, Found>,
+    error: Option<String>,
+    tried: Instant,
+}
+
+impl Discovery {
+    pub fn start() -> Self {
+        let mut discovery = Discovery { incoming: None, found: BTreeMap::new(), error: None, tried: Instant::now() };
+        discovery.listen();
+        discovery
+    }
+
+    fn listen(&mut self) {
+        self.tried = Instant::now();
+        match listen() {
+            Ok(incoming) => {
+                self.incoming = Some(incoming);
+                self.error = None;
+            }
+            Err(e) if e.kind() == ErrorKind::AddrInUse => {
+                self.error = Some(format!("UDP port {} is in use (another client on this computer?)", DISCOVERY_PORT));
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    // 찾기를 못 하고 있으면 그 까닭
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    // 버전이 다른 서버는 들어가도 Hello 에서 거절되므로 보이지 않는다
+    pub fn update(&mut self) {
+        if self.incoming.is_none() && self.tried.elapsed() >= BIND_AGAIN {
+            self.listen();
+        }
+        if let Some(incoming) = &self.incoming {
+            for (addr, beacon) in incoming.try_iter() {
+                if beacon.version == VERSION {
+                    self.found.insert(addr, Found { addr, beacon, seen: Instant::now() });
+                }
+            }
+        }
+        self.found.retain(|_, found| found.seen.elapsed() < FORGET_AFTER);
+    }
+
+    pub fn found(&self) -> Vec<&Found> {
+        self.found.values().collect()
+    }
+}
+
+fn listen() -> io::Result<Receiver<(SocketAddr, Beacon)>> {
+    let socket = UdpSocket::bind(("0.0.0.0", DISCOVERY_PORT))?;
+    let (found, incoming) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = [0u8; 2048];
+        loop {
+            let (len, from) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+                // 소켓이 계속 실패해도 CPU 를 다 쓰지 않도록 잠시 쉬었다 다시 받는다
+                Err(e) => {
+                    println!("LAN discovery failed to receive: {}", e);
+                    thread::sleep(RETRY_AFTER);
+                    continue;
+                }
+            };
+            if let Ok(beacon) = Beacon::decode(&buf[..len]) {
+                if found.send((SocketAddr::new(from.ip(), beacon.port), beacon)).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    Ok(incoming)
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use maze_core::Outcome;
+
+const LEADERBOARD_FILE: &str = "leaderboard.txt";
+const TOP: usize = 10;
+
+// 한 줄에 한 판, 탭으로 나눈다
+//   name  outcome  time  penalty  seed  width  height  algorithm  mode
+// time 에는 힌트 벌점(penalty)이 이미 더해져 있다.
+// seed 가 없는 판(Join 으로 받은 미로)은 '-' 로 적는다
+pub(crate) struct Record {
+    pub(crate) name: String,
+    pub(crate) outcome: Outcome,
+    pub(crate) time: f32,
+    pub(crate) penalty: f32,
+    pub(crate) seed: Option<u64>,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) algorithm: String,
+    pub(crate) mode: String,
+}
+
+impl Record {
+    fn to_line(&self) -> String {
+        let seed = self.seed.map_or("-".to_string(), |seed| seed.to_string());
+        let outcome = match self.outcome {
+            Outcome::Escaped => "escaped",
+            Outcome::Bombed => "bombed",
+            Outcome::Playing => "playing",
+        };
+        let name: String = self.name.chars().filter(|c| !c.is_control()).collect();
+        format!("{}\t{}\t{:.3}\t{:.3}\t{}\t{}\t{}\t{}\t{}", name, outcome, self.time, self.penalty, seed, self.width, self.height, self.algorithm, self.mode)
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 9 {
+            return None;
+        }
+        let outcome = match fields[1] {
+            "escaped" => Outcome::Escaped,
+            "bombed" => Outcome::Bombed,
+            _ => return None,
+        };
+        Some(Record {
+            name: fields[0].to_string(),
+            outcome,
+            time: fields[2].parse().ok()?,
+            penalty: fields[3].parse().ok()?,
+            seed: if fields[4] == "-" { None } else { Some(fields[4].parse().ok()?) },
+            width: fields[5].parse().ok()?,
+            height: fields[6].parse().ok()?,
+            algorithm: fields[7].to_string(),
+            mode: fields[8].to_string(),
+        })
+    }
+
+    // 같은 모드, 크기, 알고리즘끼리 순위를 매긴다
+    pub(crate) fn category(&self) -> String {
+        format!("{} {}x{} {}", self.mode, self.width, self.height, self.algorithm)
+    }
+}
+
+pub(crate) struct Leaderboard {
+    path: PathBuf,
+    records: Vec<Record>,
+}
+
+impl Leaderboard {
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(LEADERBOARD_FILE);
+        let records = match fs::read_to_string(&path) {
+            Ok(text) => text.lines().filter(|line| !line.starts_with('#')).filter_map(Record::parse).collect(),
+            Err(_) => Vec::new(),
+        };
+        Leaderboard { path, records }
+    }
+
+    // 덮어쓰지 않고 파일 끝에 붙인다
+    pub fn record(&mut self, record: Record) {
+        let result = self.path.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| {
+            let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+            writeln!(file, "{}", record.to_line())
+        });
+        if let Err(e) = result {
+            println!("failed to save score to {}: {}", self.path.display(), e);
+        }
+        self.records.push(record);
+    }
+
+    pub fn categories(&self) -> Vec<String> {
+        let mut categories: Vec<String> = self.records.iter().map(Record::category).collect();
+        categories.sort();
+        categories.dedup();
+        categories
+    }
+
+    // 탈출한 판을 빠른 순서로, 그 뒤에 폭탄에 맞은 판
+    pub fn top(&self, category: &str) -> Vec<&Record> {
+        let mut records: Vec<&Record> = self.records.iter().filter(|record| record.category() == category).collect();
+        records.sort_by(|a, b| {
+            (a.outcome != Outcome::Escaped).cmp(&(b.outcome != Outcome::Escaped)).then(a.time.total_cmp(&b.time))
+        });
+        records.truncate(TOP);
+        records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, outcome: Outcome, time: f32) -> Record {
+        Record {
+            name: name.to_string(),
+            outcome,
+            time,
+            penalty: 0.0,
+            seed: Some(7),
+            width: 30,
+            height: 30,
+            algorithm: "dfs".to_string(),
+            mode: "solo".to_string(),
+        }
+    }
+
+    fn board(records: Vec<Record>) -> Leaderboard {
+        Leaderboard { path: PathBuf::new(), records }
+    }
+
+    #[test]
+    fn records_survive_a_round_trip() {
+        let mut original = record("kim\tlee", Outcome::Bombed, 12.5);
+        original.penalty = 10.0;
+        original.seed = None;
+        let parsed = Record::parse(&original.to_line()).unwrap();
+        // 탭은 칸을 나누므로 이름에서 빠진다
+        assert_eq!(parsed.name, "kimlee");
+        assert_eq!(parsed.outcome, Outcome::Bombed);
+        assert_eq!(parsed.time, 12.5);
+        assert_eq!(parsed.penalty, 10.0);
+        assert_eq!(parsed.seed, None);
+        assert_eq!((parsed.width, parsed.height), (30, 30));
+        assert_eq!((parsed.algorithm.as_str(), parsed.mode.as_str()), ("dfs", "solo"));
+
+        let parsed = Record::parse(&record("park", Outcome::Escaped, 3.0).to_line()).unwrap();
+        assert_eq!(parsed.seed, Some(7));
+        assert_eq!(parsed.outcome, Outcome::Escaped);
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        assert!(Record::parse("").is_none());
+        assert!(Record::parse("kim\tescaped\t1.0").is_none());
+        assert!(Record::parse("kim\tplaying\t1.0\t0.0\t7\t30\t30\tdfs\tsolo").is_none());
+        assert!(Record::parse("kim\tescaped\tfast\t0.0\t7\t30\t30\tdfs\tsolo").is_none());
+        assert!(Record::parse("kim\tescaped\t1.0\t0.0\tseven\t30\t30\tdfs\tsolo").is_none());
+        assert!(Record::parse("kim\tescaped\t1.0\t0.0\t7\t30\t30\tdfs\tsolo\textra").is_none());
+    }
+
+    #[test]
+    fn escapes_rank_before_bombs_then_by_time() {
+        let board = board(vec![
+            record("bombed fast", Outcome::Bombed, 1.0),
+            record("slow", Outcome::Escaped, 20.0),
+            record("first tie", Outcome::Escaped, 5.0),
+            record("second tie", Outcome::Escaped, 5.0),
+            record("bombed slow", Outcome::Bombed, 9.0),
+        ]);
+        let category = board.records[0].category();
+        let names: Vec<&str> = board.top(&category).iter().map(|record| record.name.as_str()).collect();
+        // 같은 시간이면 먼저 남긴 기록이 앞선다
+        assert_eq!(names, ["first tie", "second tie", "slow", "bombed fast", "bombed slow"]);
+    }
+
+    #[test]
+    fn only_the_top_of_one_category_is_shown() {
+        let mut records: Vec<Record> = (0..TOP + 5).map(|i| record("kim", Outcome::Escaped, i as f32)).collect();
+        let mut other = record("lee", Outcome::Escaped, 0.5);
+        other.width = 15;
+        records.push(other);
+        let board = board(records);
+        let top = board.top("solo 30x30 dfs");
+        assert_eq!(top.len(), TOP);
+        assert!(top.iter().all(|record| record.name == "kim"));
+        assert_eq!(board.categories(), ["solo 15x30 dfs", "solo 30x30 dfs"]);
+    }
+}
@@ -1,215 +1,632 @@
-mod server;
+mod config;
+mod discovery;
+mod leaderboard;
+mod net;
 
 use ggez::{Context, ContextBuilder, GameError, GameResult, input::keyboard::KeyCode};
-use ggez::graphics::{self, Color, Rect};
+use ggez::graphics::{self, Color, ImageFormat, Rect, Sampler};
 use ggez::event::{self, EventHandler};
 use ggez::input::keyboard::KeyInput;
 
-use oorandom::Rand32;
-
-use rand::{Rng, thread_rng};
-use rand::prelude::ThreadRng;
-use rand::seq::SliceRandom;
-
-use std::collections::VecDeque;
-use std::net::{Shutdown, TcpListener, TcpStream}; // line 10..12 socket, thread 관련 lib 추가
-use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
-use std::{io, thread};
-use std::fs::File;
-use std::str::from_utf8;
-
-const MAP_SIZE: usize = 30;
-const WALL: char = '#';
-const PLAYER: char = 'P';
-const EXIT: char = 'E';
-const BOMB: char = '*';
-static mut player_row: usize = 0;
-static mut player_col: usize = 0;
-static mut bomb_row: usize = 0;
-static mut bomb_col: usize = 0;
-static mut exit_row: usize = 0;
-static mut exit_col: usize = 0;
-static mut server_true: bool = false;
-static mut socket_client: Option<TcpStream> = None;
-unsafe fn connect2server() {
-    socket_client = (TcpStream::connect("127.0.0.1:8088")
-        .map_err(|e| {
-            io::Error::new(
-                ErrorKind::Other,
-                format!("Failed to connect to server: {}", e),
-            )
-        })
-        .ok());
-}
-
-
-unsafe fn init_map() -> Vec<Vec<char>> {
-
-    // 미로를 나타내는 2차원 벡터 생성
-    let mut map = vec![vec!['#'; MAP_SIZE]; MAP_SIZE];
-
-    // 시작 지점을 무작위로 선택
-    let mut rng = thread_rng();
-
-    exit_row = MAP_SIZE - 1;
-    exit_col = MAP_SIZE - 1;
-    map[exit_row][exit_col] = EXIT;
-    // set player
-    player_row = rng.gen_range(0..MAP_SIZE).max(1);
-    player_col = rng.gen_range(0..MAP_SIZE).max(1);
-    while map[player_row][player_col] != '#' {
-        player_row = rng.gen_range(0..MAP_SIZE);
-        player_col = rng.gen_range(0..MAP_SIZE);
-    }
-    map[player_row][player_col] = PLAYER;
-    // set bomb
-    bomb_row = rng.gen_range(0..MAP_SIZE);
-    bomb_col = rng.gen_range(0..MAP_SIZE);
-    while map[bomb_row][bomb_col] != '#' {
-        bomb_row = rng.gen_range(0..MAP_SIZE);
-        bomb_col = rng.gen_range(0..MAP_SIZE);
-    }
-    map[bomb_row][bomb_col] = BOMB;
-
-
-    dfs(&mut map, player_row, player_col, &mut rng);
-
-    if !is_reachable(&map, player_row, player_col) {
-        map = init_map();
-    }
-
-    map
-}
-
-fn dfs(maze: &mut Vec<Vec<char>>, row: usize, col: usize, rng: &mut ThreadRng) {
-    let mut directions = vec![(0, -2), (0, 2), (-2, 0), (2, 0)];
-    directions.shuffle(rng);
-    for (dr, dc) in directions {
-        let (r, c) = (row as i32 + dr, col as i32 + dc);
-        if r < 1 || r >= (MAP_SIZE) as i32 || c < 1 || c >= (MAP_SIZE) as i32 {
-            continue;
-        }
-        let (r, c) = (r as usize, c as usize);
-        if maze[r][c] == '#' {
-            maze[(row as i32 + dr / 2) as usize][(col as i32 + dc / 2) as usize] = ' ';
-            maze[r][c] = ' ';
-            dfs(maze, r, c, rng);
-        }
-    }
-}
-
-fn is_reachable(maze: &Vec<Vec<char>>, start_row: usize, start_col: usize) -> bool {
-    let mut visited = vec![vec![false; MAP_SIZE]; MAP_SIZE];
-    let mut queue = VecDeque::new();
-    queue.push_back((start_row, start_col));
-    visited[start_row][start_col] = true;
-
-    while let Some((row, col)) = queue.pop_front() {
-        if row == MAP_SIZE - 1 && col == MAP_SIZE - 1 {
-            return true;
-        }
-        for (dr, dc) in &[(0, -1), (0, 1), (-1, 0), (1, 0)] {
-            let (r, c) = ((row as i32 + dr) as usize, (col as i32 + dc) as usize);
-
-            #[allow(unused_comparisons)]
-            if r < 0 || r >= MAP_SIZE || c < 0 || c >= MAP_SIZE || visited[r][c] || maze[r][c] == '#' || maze[r][c] == '*' {
-                continue;
-            }
-
-            visited[r][c] = true;
-            queue.push_back((r, c));
-        }
-    }
-    false
+use maze_core::protocol::{self, Finish, Message, Placing, RoomInfo};
+use maze_core::enemy::{self, Enemy};
+use maze_core::{fov, generator, solver, Behavior, BombRules, EnemyRules, GameState, Outcome, Strategy, DIFFICULTIES};
+
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 메뉴에서 고를 수 있는 미로 크기 (너비, 높이). 그 밖의 크기는 custom 으로 입력한다
+const SIZES: [(&str, usize, usize); 3] = [("small", 15, 15), ("medium", 30, 30), ("large", 50, 50)];
+const MIN_SIZE: usize = 5;
+const MAX_SIZE: usize = 2000;
+// 힌트는 한 판에 HINTS 번, 한 번에 HINT_STEPS 칸을 보여주고 HINT_PENALTY 초를 더한다
+const HINTS: u32 = 3;
+const HINT_STEPS: usize = 5;
+const HINT_PENALTY: f32 = 10.0;
+// 메뉴의 Sight 가 도는 값. 0 이면 미로 전체가 보인다
+const SIGHTS: [u8; 4] = [0, 3, 5, 8];
+// 최단 경로를 CHECKPOINTS 등분한 칸마다 구간 기록을 남긴다
+const CHECKPOINTS: usize = 4;
+// 폭발 그림이 남아 있는 초
+const BLAST_TIME: f32 = 0.8;
+// 서버가 살아 있는지 확인하는 간격
+const HEARTBEAT: Duration = Duration::from_secs(1);
+// 판 도중 끊기면 RECONNECT_TRY 마다 다시 접속해 보고, 서버가 자리를 맡아 주는 동안만 기다린다
+const RECONNECT_TRY: Duration = Duration::from_secs(2);
+const RECONNECT_WINDOW: Duration = Duration::from_secs(30);
+// 멀티에서 플레이어 번호별 색 (나는 항상 GREEN)
+const RACER_COLORS: [Color; 8] = [
+    Color::BLUE,
+    Color::MAGENTA,
+    Color::CYAN,
+    Color::new(1.0, 0.5, 0.0, 1.0),
+    Color::new(0.5, 0.0, 1.0, 1.0),
+    Color::new(0.6, 0.4, 0.2, 1.0),
+    Color::new(1.0, 0.6, 0.8, 1.0),
+    Color::new(0.5, 0.5, 0.5, 1.0),
+];
+// 열쇠와 짝 문의 색. maze_core::KEYS 순서
+const KEY_COLORS: [Color; 3] = [
+    Color::new(1.0, 0.8, 0.0, 1.0),
+    Color::new(0.0, 0.6, 0.5, 1.0),
+    Color::new(0.9, 0.3, 0.6, 1.0),
+];
+// 적의 색. maze_core::enemy::BEHAVIORS 순서 (patrol, chase, ambush)
+const ENEMY_COLORS: [Color; 3] = [
+    Color::new(0.9, 0.4, 0.1, 1.0),
+    Color::new(0.6, 0.0, 0.0, 1.0),
+    Color::new(0.4, 0.0, 0.5, 1.0),
+];
+
+fn random_seed() -> u64 {
+    let mut buf = [0u8; 8];
+    getrandom::getrandom(&mut buf).expect("failed to read OS randomness");
+    u64::from_le_bytes(buf)
 }
 
 struct MyGame {
     wall: Wall,
-    map: Vec<Vec<char>>,
+    game: GameState,
     player: Player,
-    bomb: Bomb,
+    bombs: Vec<Bomb>,
+    // 혼자 할 때는 직접 움직이고, 멀티에서는 서버가 Enemies 로 알려 준 자리에 그린다
+    enemies: Vec<Enemy>,
+    enemy_clock: f32,
     exit: Exit,
+    hint: Hint,
+    fog: Fog,
+    checkpoints: Vec<(usize, usize)>,
+    // 혼자 할 때 큰 미로도 창이 멈추지 않도록 미로와 적은 따로 스레드에서 만든다
+    generating: Option<Receiver<(GameState, Vec<Enemy>)>>,
     draw_menu: Menu,
     solo:bool,
-    mulit_player:Player,
-    socket_client: Option<TcpStream>,
+    // 멀티: 나를 포함한 모든 플레이어
+    racers: BTreeMap<u8, Racer>,
+    // 서버가 GameOver 로 보낸 순위 (결과 화면)
+    results: Option<Vec<Placing>>,
+    saved: bool,
+    socket_client: Option<net::Connection>,
+    // 서버가 정해 준 내 플레이어 번호
+    me: Option<u8>,
+    // 끊겼을 때 같은 자리로 돌아가기 위한 표
+    token: Option<u64>,
+    reconnect: Option<Reconnect>,
+    heartbeat: Instant,
+    // 서버가 미로를 보내 주기를 기다리는 중
+    waiting: bool,
+    // 서버가 Go 를 보낼 때까지 입력과 시계를 멈춘다. countdown 은 화면에 띄울 숫자 (0 이면 GO)
+    locked: bool,
+    countdown: Option<u8>,
+    // Join: 로비에서 방 목록을 보는 중
+    browsing: bool,
+    // 자리 없이 남의 판을 구경하는 중
+    spectating: bool,
+    rooms: Vec<RoomInfo>,
+    room_select: usize,
+    // 서버가 거절한 이유 등, 메뉴와 로비에 띄우는 한 줄
+    notice: String,
+    discovery: discovery::Discovery,
+    lan_select: Option<usize>,
     first: bool,
-    timer: timer,
+    timer: Timer,
     end: bool,
+    algorithm: &'static str,
+    // 혼자 할 때의 폭탄 규칙 (멀티에서는 서버가 Fuse 로 알려 준다)
+    bomb_rules: BombRules,
+    enemy_rules: EnemyRules,
+    name: String,
+    leaderboard: leaderboard::Leaderboard,
 }
 
 impl MyGame {
-    pub unsafe fn new(
-        x: &mut Context) -> Self {
-        let wall_pos = GridPosition { x: 0, y: 0 };
+    pub fn new(
+        ctx: &mut Context) -> Self {
+        let seed = random_seed();
+        let config = config::Config::load();
+        let algorithm = generator::ALGORITHMS.iter().position(|&name| name == config.algorithm).unwrap_or_else(|| {
+            println!("unknown maze algorithm '{}', using dfs", config.algorithm);
+            0
+        });
+        let generator = generator::by_name(generator::ALGORITHMS[algorithm]).unwrap();
+        let mut draw_menu = Menu::new(0, vec!["Solo".to_string(), "Multi".to_string(), "Join".to_string(), "Room".to_string(), "Players".to_string(), "Seed".to_string(), "Algorithm".to_string(), "Size".to_string(), "Difficulty".to_string(), "Sight".to_string(), "Leaderboard".to_string(), "Exit".to_string()]);
+        draw_menu.algorithm = algorithm;
+        draw_menu.room_input = protocol::short_name(&config.name);
+        draw_menu.server_input = config.server;
+        draw_menu.set_size(config.width, config.height);
+        draw_menu.difficulty = DIFFICULTIES.iter().position(|&difficulty| difficulty == config.difficulty).unwrap_or(1);
+        draw_menu.bomb_count = config.bombs;
+        draw_menu.door_count = config.doors;
+        draw_menu.enemy_kinds = config.enemies.clone();
+        draw_menu.sight = config.sight;
+        // 메뉴 뒤에 놓아 둘 작은 미로. 실제 판은 start_round 가 메뉴 설정대로 만든다
+        let game = GameState::generate(seed, generator.as_ref(), MIN_SIZE, MIN_SIZE, 0, 0);
         MyGame {
-            wall: Wall::new(wall_pos, false),
-            map: init_map(),
-            player: Player::new(GridPosition { x: (player_row) as i16, y: (player_col) as i16 }),
-            bomb: Bomb::new(GridPosition { x: (bomb_row) as i16, y: (bomb_col) as i16 }),
-            exit: Exit::new(GridPosition { x: (exit_row  ) as i16, y: (exit_col  )as i16 }),
-            draw_menu: Menu::new(0, vec!["Solo".to_string(), "Multi".to_string(), "Join".to_string(), "Exit".to_string()]),
+            wall: Wall::new(false),
+            player: Player::new(game.player.into(), Color::GREEN),
+            bombs: game.bombs.iter().map(|&pos| Bomb::new(pos.into(), config.bomb)).collect(),
+            enemies: Vec::new(),
+            enemy_clock: 0.0,
+            exit: Exit::new(game.exit.into()),
+            hint: Hint::new(),
+            fog: Fog::new(config.sight),
+            checkpoints: Vec::new(),
+            generating: None,
+            draw_menu,
             solo: true,
-            mulit_player: Player::new(GridPosition { x: (player_row) as i16, y: (player_col) as i16 }),
+            racers: BTreeMap::new(),
+            results: None,
+            saved: false,
             socket_client: None,
+            me: None,
+            token: None,
+            reconnect: None,
+            heartbeat: Instant::now(),
+            waiting: false,
+            locked: false,
+            countdown: None,
+            browsing: false,
+            spectating: false,
+            rooms: Vec::new(),
+            room_select: 0,
+            notice: String::new(),
+            discovery: discovery::Discovery::start(),
+            lan_select: None,
             first: true,
-            timer: timer::new(),
+            timer: Timer::new(),
             end: false,
+            algorithm: generator.name(),
+            bomb_rules: config.bomb,
+            enemy_rules: config.enemy,
+            name: config.name,
+            leaderboard: leaderboard::Leaderboard::load(ctx.fs.user_data_dir()),
+            game,
         }
     }
-    fn update_multi(&mut self,solo:bool){
-        self.solo = solo;
-    }
-    fn make_socket_server(&mut self, user_type:bool) -> io::Result<()>{
-        if user_type{
-
-
-        }
-        Ok(())
-
+    fn start_round(&mut self, seed: u64) {
+        let name = generator::ALGORITHMS[self.draw_menu.algorithm];
+        let (width, height) = self.draw_menu.size();
+        let (bombs, doors, kinds) = (self.draw_menu.bombs(), self.draw_menu.doors(), self.draw_menu.enemies());
+        self.algorithm = name;
+        self.fog.sight = self.draw_menu.sight;
+        let (done, generating) = mpsc::channel();
+        thread::spawn(move || {
+            let generator = generator::by_name(name).unwrap();
+            let game = GameState::generate(seed, generator.as_ref(), width, height, bombs, doors);
+            let enemies = enemy::spawn(&game, &kinds, seed);
+            let _ = done.send((game, enemies));
+        });
+        self.generating = Some(generating);
     }
-    fn update_map(mut self, map:Vec<Vec<char>>){
-        self.map = map;
+    // 미로가 다 만들어졌으면 판을 연다
+    fn collect_round(&mut self) {
+        let (game, enemies) = match self.generating.as_ref().map(Receiver::try_recv) {
+            Some(Ok(round)) => round,
+            Some(Err(TryRecvError::Empty)) | None => return,
+            Some(Err(TryRecvError::Disconnected)) => {
+                self.generating = None;
+                self.notice = "Failed to generate the maze".to_string();
+                self.back_to_menu();
+                return;
+            }
+        };
+        self.generating = None;
+        self.set_game(game);
+        self.enemies = enemies;
     }
-    fn handle_client(mut stream: TcpStream){
-        let mut data = [0 as u8; 50]; // using 50 byte buffer
-        while match stream.read(&mut data) {
-            Ok(size) => {
-                // echo everything!
-                // stream.write(&data[0..size]).unwrap();
-                println!("{}", String::from_utf8_lossy(&data[0..size]));
-                true
+    // 혼자 할 때 적을 서버처럼 step_ms 마다 한 칸씩 움직인다
+    fn hunt(&mut self, dt: f32) {
+        if self.enemies.is_empty() {
+            return;
+        }
+        let step = self.enemy_rules.step_ms as f32 / 1000.0;
+        self.enemy_clock += dt;
+        while self.enemy_clock >= step && self.game.outcome == Outcome::Playing {
+            self.enemy_clock -= step;
+            for enemy in &mut self.enemies {
+                enemy.step(&self.game.map, &[self.game.player], self.game.exit, &self.enemy_rules);
             }
-            Err(_) => {
-                println!("An error occurred, terminating connection with {}", stream.peer_addr().unwrap());
-                stream.shutdown(Shutdown::Both).unwrap();
-                false
+            if enemy::caught(&self.enemies, self.game.player) {
+                self.game.outcome = Outcome::Bombed;
             }
-        } {}
+        }
+    }
+    fn draw_enemies(&self, canvas: &mut graphics::Canvas, size: (usize, usize)) {
+        let view = View::new(size);
+        for enemy in &self.enemies {
+            let rect = view.rect(enemy.pos.0 as i16, enemy.pos.1 as i16);
+            let color = ENEMY_COLORS[enemy::BEHAVIORS.iter().position(|&b| b == enemy.behavior).unwrap_or(0)];
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(Rect::new(rect.x + rect.w * 0.15, rect.y + rect.h * 0.15, rect.w * 0.7, rect.h * 0.7))
+                    .color(color), );
+        }
+    }
+    // 구경꾼과 끝난 판은 미로 전체를 본다
+    fn in_the_dark(&self) -> bool {
+        !self.spectating && !self.browsing && self.results.is_none() && self.game.outcome == Outcome::Playing
+    }
+    // 순위는 서버가 잰 시간으로 정하므로 힌트는 혼자 할 때만 쓸 수 있다
+    fn use_hint(&mut self) {
+        if !self.solo || self.hint.left == 0 || self.spectating || self.locked || self.timer.paused || self.waiting || self.generating.is_some() || self.game.outcome != Outcome::Playing {
+            return;
+        }
+        if let Some(path) = self.game.solve(Strategy::Bfs) {
+            self.hint.show(&path);
+            self.timer.add_penalty(HINT_PENALTY);
+        }
+    }
+    fn set_game(&mut self, game: GameState) {
+        self.player.update_pos(game.player.into());
+        self.bombs = game.bombs.iter().map(|&pos| Bomb::new(pos.into(), self.bomb_rules)).collect();
+        self.exit.pos = game.exit.into();
+        self.hint = Hint::new();
+        self.fog.reset();
+        self.enemies.clear();
+        self.enemy_clock = 0.0;
+        self.timer = Timer::new();
+        self.saved = false;
+        self.results = None;
+        self.end = false;
+        self.locked = false;
+        self.countdown = None;
+        // 미로 대부분은 길이 하나뿐이라 출구로 가려면 이 칸들을 반드시 지난다
+        self.checkpoints = match game.solve(Strategy::Bfs) {
+            Some(path) => (1..CHECKPOINTS).map(|k| path[path.len() * k / CHECKPOINTS]).collect(),
+            None => Vec::new(),
+        };
+        self.game = game;
     }
     fn client_connect(&mut self, url: &str, task:bool){
         if task{
-            self.socket_client = (TcpStream::connect(url)
-                .map_err(|e| {
-                    io::Error::new(
-                        ErrorKind::Other,
-                        format!("Failed to connect to server: {}", e),
-                    )
-                })
-                .ok());
+            self.socket_client = Some(net::Connection::connect(url));
         }else{
             drop(self.socket_client.take());
         }
 
     }
-
+    fn send(&self, message: Message) {
+        if let Some(server_socket) = &self.socket_client {
+            server_socket.send(message);
+        }
+    }
+    // 내가 한 칸 움직인 뒤 (멀티에서는 서버가 확인해 준 뒤)
+    fn moved(&mut self) {
+        self.player.update_pos(self.game.player.into());
+        self.hint.moved(&self.player.pos);
+        if let Some(index) = self.checkpoints.iter().position(|&pos| pos == self.game.player) {
+            self.checkpoints.drain(..=index);
+            self.timer.split();
+        }
+    }
+    // 도착한 메시지만 처리하고 바로 돌아온다
+    fn receive(&mut self) {
+        while let Some(event) = self.socket_client.as_ref().and_then(net::Connection::try_recv) {
+            match event {
+                net::Event::Message(Message::RoomList { rooms }) => {
+                    self.room_select = self.room_select.min(rooms.len().saturating_sub(1));
+                    self.rooms = rooms;
+                }
+                // 다시 들어온 것이면 판은 그대로 두고 서버가 보내 주는 상태만 따른다
+                net::Event::Message(Message::Welcome { player, token }) if self.reconnect.is_some() => {
+                    self.reconnect = None;
+                    self.me = Some(player);
+                    self.token = Some(token);
+                    if self.locked {
+                        self.send(Message::Ready);
+                    }
+                }
+                net::Event::Message(Message::Refused { reason }) if self.reconnect.is_some() => {
+                    self.notice = reason;
+                    self.leave_server();
+                }
+                net::Event::Message(Message::Welcome { player, token }) => {
+                    self.me = Some(player);
+                    self.token = Some(token);
+                    self.racers.clear();
+                    self.browsing = false;
+                    self.spectating = false;
+                    self.waiting = true;
+                }
+                // 만들려던 방이 이미 있으면 메뉴로, 들어가려던 방이 없거나 찼으면 목록으로
+                net::Event::Message(Message::Refused { reason }) => {
+                    self.notice = reason;
+                    if self.draw_menu.user_type {
+                        self.leave_server();
+                    } else {
+                        self.browsing = true;
+                        self.waiting = false;
+                        self.spectating = false;
+                        self.send(Message::ListRooms);
+                    }
+                }
+                net::Event::Message(Message::MazeData { map }) if self.waiting => {
+                    self.waiting = false;
+                    self.set_game(GameState::from_map(map));
+                    self.locked = true;
+                    if !self.spectating {
+                        self.send(Message::Ready);
+                    }
+                }
+                net::Event::Message(Message::Countdown { seconds }) => {
+                    self.locked = true;
+                    self.countdown = Some(seconds);
+                }
+                net::Event::Message(Message::Fuse { ms, radius }) => {
+                    for bomb in &mut self.bombs {
+                        bomb.timer = ms as f32 / 1000.0;
+                        bomb.radius = radius;
+                    }
+                }
+                net::Event::Message(Message::Sight { radius }) => self.fog.sight = radius,
+                net::Event::Message(Message::Enemies { enemies }) => {
+                    self.enemies = enemies.into_iter().map(|(behavior, x, y)| Enemy::new(behavior, (x as usize, y as usize))).collect();
+                }
+                // 잡혔는지는 서버가 Bombed 로 따로 알려 준다
+                net::Event::Message(Message::Exploded { x, y, radius }) => {
+                    self.game.explode((x as usize, y as usize), radius);
+                    let pos: GridPosition = (x as usize, y as usize).into();
+                    if let Some(bomb) = self.bombs.iter_mut().find(|bomb| bomb.pos == pos) {
+                        bomb.boom();
+                    }
+                }
+                net::Event::Message(Message::Go) => {
+                    self.locked = false;
+                    self.countdown = Some(0);
+                }
+                // 위치와 승패는 서버가 보낸 대로 따른다
+                net::Event::Message(Message::PlayerMove { player, x, y }) => {
+                    let pos = (x as usize, y as usize);
+                    self.racer(player).player.update_pos(pos.into());
+                    if Some(player) == self.me && pos != self.game.player {
+                        self.game.enter(pos);
+                        self.moved();
+                    }
+                }
+                net::Event::Message(Message::PlayerFinished { player, time_ms }) => {
+                    let racer = self.racer(player);
+                    racer.outcome = Outcome::Escaped;
+                    // 기록은 서버가 잰 시간 그대로 (멀티에서는 힌트를 쓸 수 없다)
+                    if Some(player) == self.me {
+                        self.timer.time = time_ms as f32 / 1000.0;
+                        self.game.outcome = Outcome::Escaped;
+                    }
+                }
+                net::Event::Message(Message::Bombed { player, .. }) => {
+                    self.racer(player).outcome = Outcome::Bombed;
+                    if Some(player) == self.me {
+                        self.game.outcome = Outcome::Bombed;
+                    }
+                }
+                net::Event::Message(Message::PlayerAway { player }) => self.racer(player).away = true,
+                net::Event::Message(Message::PlayerBack { player }) => self.racer(player).away = false,
+                net::Event::Message(Message::PlayerLeft { player }) => {
+                    let racer = self.racer(player);
+                    racer.away = false;
+                    racer.left = true;
+                }
+                net::Event::Message(Message::GameOver { results }) => self.results = Some(results),
+                net::Event::Message(Message::RematchVote { player }) => self.racer(player).rematch = true,
+                // 같은 방에서 한 판 더. 자리가 다 차면 서버가 새 미로를 보낸다
+                net::Event::Message(Message::Rematch) => {
+                    self.results = None;
+                    self.racers.clear();
+                    self.countdown = None;
+                    self.waiting = true;
+                }
+                net::Event::Message(Message::Chat { text }) => println!("opponent: {}", text),
+                net::Event::Message(Message::Ping { nonce }) => self.send(Message::Pong { nonce }),
+                net::Event::Message(_) => {}
+                net::Event::Closed(e) => {
+                    println!("{}", e);
+                    self.socket_client = None;
+                    // 판 도중이면 같은 자리로 돌아가 본다. 미로를 받기 전에 끊겼으면 할 수 있는 게 없다
+                    let racing = self.me.is_some() && self.token.is_some() && !self.waiting && self.results.is_none();
+                    if self.reconnect.is_none() && racing {
+                        let now = Instant::now();
+                        self.reconnect = Some(Reconnect { since: now, next_try: now });
+                    } else if self.waiting || self.browsing || self.spectating {
+                        self.notice = e;
+                        self.leave_server();
+                    }
+                }
+            }
+        }
+    }
+    // 접속이 끊긴 동안 RECONNECT_TRY 마다 새로 접속해 Rejoin 을 보낸다
+    fn try_reconnect(&mut self) {
+        let (since, next_try) = match &self.reconnect {
+            Some(reconnect) => (reconnect.since, reconnect.next_try),
+            None => return,
+        };
+        if since.elapsed() >= RECONNECT_WINDOW {
+            self.notice = "Lost connection to the server".to_string();
+            self.leave_server();
+            return;
+        }
+        if self.socket_client.is_some() || Instant::now() < next_try {
+            return;
+        }
+        if let (Some(reconnect), Some(token)) = (self.reconnect.as_mut(), self.token) {
+            reconnect.next_try = Instant::now() + RECONNECT_TRY;
+            let server = self.draw_menu.server();
+            self.client_connect(&server, true);
+            self.send(Message::Rejoin { token });
+        }
+    }
+    // 나 말고 모두 나갔으면 혼자 끝까지 가거나 메뉴로 돌아갈 수 있다
+    fn opponents_left(&self) -> bool {
+        let mut others = self.racers.iter().filter(|(&number, _)| Some(number) != self.me).peekable();
+        others.peek().is_some() && others.all(|(_, racer)| racer.left)
+    }
+    fn racer(&mut self, player: u8) -> &mut Racer {
+        self.racers.entry(player).or_insert_with(|| Racer::new(player))
+    }
+    fn leave_server(&mut self) {
+        self.client_connect("",false);
+        self.reconnect = None;
+        self.waiting = false;
+        self.spectating = false;
+        self.browsing = false;
+        self.back_to_menu();
+    }
+    // 방에서 나와 방 목록을 본다. 미로는 다음 방의 MazeData 가 올 때까지 감춘다
+    fn back_to_lobby(&mut self) {
+        self.results = None;
+        self.racers.clear();
+        self.enemies.clear();
+        self.hint = Hint::new();
+        self.me = None;
+        self.token = None;
+        self.end = false;
+        self.locked = false;
+        self.countdown = None;
+        self.waiting = false;
+        self.spectating = false;
+        // 로비에서 거절당해도 메뉴로 쫓겨나지 않도록 Join 처럼 다룬다
+        self.draw_menu.user_type = false;
+        self.browsing = true;
+        self.wall.update(false);
+        self.player.update(false);
+        self.exit.update(false);
+        for bomb in &mut self.bombs {
+            bomb.update(false);
+        }
+        self.send(Message::ListRooms);
+    }
+    fn back_to_menu(&mut self) {
+        self.end = false;
+        self.results = None;
+        self.draw_menu.in_menu = true;
+        self.first = true;
+        self.wall.update(false);
+        self.player.update(false);
+        self.exit.update(false);
+        for bomb in &mut self.bombs {
+            bomb.update(false);
+        }
+    }
     fn end_game(&mut self) {
-        if self.map[self.player.pos.x as usize][self.player.pos.y as usize] == 'E'{
-            self.end = true;
-        }else if self.map[self.player.pos.x as usize][self.player.pos.y as usize] == '*'{
+        if !self.waiting && self.game.outcome != Outcome::Playing {
             self.end = true;
         }
     }
+    fn draw_lan(&self, canvas: &mut graphics::Canvas) {
+        let found = self.discovery.found();
+        let mut lines = vec!["LAN games (Enter to edit, then Up/Down)".to_string()];
+        if let Some(error) = self.discovery.error() {
+            lines.push(format!("LAN discovery unavailable: {}", error));
+        } else if found.is_empty() {
+            lines.push("none found".to_string());
+        }
+        for (i, server) in found.iter().enumerate() {
+            let cursor = if self.lan_select == Some(i) && self.draw_menu.typing { ">" } else { " " };
+            lines.push(format!("{} {} ({}) players {}", cursor, server.beacon.name, server.addr, server.beacon.players));
+            for room in &server.beacon.rooms {
+                let state = if room.playing { "playing" } else { "waiting" };
+                lines.push(format!("    {} {}/{} {}x{} {}", room.name, room.players, room.capacity, room.width, room.height, state));
+            }
+        }
+        let mut y = 500.0;
+        for line in lines {
+            canvas.draw(
+                &graphics::Text::new(line),
+                graphics::DrawParam::new()
+                    .dest([1300.0, y])
+                    .color(Color::BLACK)
+            );
+            y += 30.0;
+        }
+    }
+    fn draw_rooms(&self, canvas: &mut graphics::Canvas) {
+        let mut lines = vec!["Rooms (Up/Down, Enter to join, W to watch, R to refresh, Esc to leave)".to_string()];
+        if self.rooms.is_empty() {
+            lines.push("no rooms yet".to_string());
+        }
+        for (i, room) in self.rooms.iter().enumerate() {
+            let cursor = if i == self.room_select { ">" } else { " " };
+            let state = if room.playing { "playing" } else { "waiting" };
+            lines.push(format!("{} {:<32} {}/{} {}x{} {}", cursor, room.name, room.players, room.capacity, room.width, room.height, state));
+        }
+        let mut y = 400.0;
+        for line in lines {
+            canvas.draw(
+                &graphics::Text::new(line),
+                graphics::DrawParam::new()
+                    .dest([750.0, y])
+                    .color(Color::BLACK)
+            );
+            y += 30.0;
+        }
+    }
+    // 주운 열쇠를 왼쪽 위 시간 아래에
+    fn draw_keys(&self, canvas: &mut graphics::Canvas) {
+        canvas.draw(
+            &graphics::Text::new("keys"),
+            graphics::DrawParam::new()
+                .dest([0.0, 60.0])
+                .color(Color::WHITE),
+        );
+        for (i, &key) in self.game.keys.iter().enumerate() {
+            if let Some(index) = solver::key_index(key) {
+                canvas.draw(
+                    &graphics::Quad,
+                    graphics::DrawParam::new()
+                        .dest_rect(Rect::new(50.0 + i as f32 * 20.0, 62.0, 14.0, 14.0))
+                        .color(KEY_COLORS[index]), );
+            }
+        }
+    }
+    fn draw_results(&self, canvas: &mut graphics::Canvas, results: &[Placing]) {
+        let who = |player: u8| if Some(player) == self.me { format!("player {} (you)", player + 1) } else { format!("player {}", player + 1) };
+        let winners: Vec<u8> = results
+            .iter()
+            .filter(|placing| placing.place == 1 && matches!(placing.finish, Finish::Escaped { .. }))
+            .map(|placing| placing.player)
+            .collect();
+        let mut lines = vec![match winners.as_slice() {
+            [] => "Nobody escaped".to_string(),
+            [player] if Some(*player) == self.me => "You win!".to_string(),
+            [player] => format!("Player {} wins", player + 1),
+            tied => format!("Tie for first: {}", tied.iter().map(|&player| who(player)).collect::<Vec<_>>().join(", ")),
+        }];
+        for placing in results {
+            let result = match placing.finish {
+                Finish::Escaped { time_ms } => format_time(time_ms as f32 / 1000.0),
+                Finish::Bombed => "bombed".to_string(),
+                Finish::Left => "left".to_string(),
+            };
+            let rematch = if self.racers.get(&placing.player).is_some_and(|racer| racer.rematch) { "  rematch!" } else { "" };
+            lines.push(format!("{}. {:<18} {}{}", placing.place, who(placing.player), result, rematch));
+        }
+        lines.push(if self.spectating { "Enter to return to the lobby" } else { "R for a rematch, Enter to return to the lobby" }.to_string());
+        let mut y = 400.0;
+        for line in lines {
+            canvas.draw(
+                &graphics::Text::new(line),
+                graphics::DrawParam::new()
+                    .dest([750.0, y])
+                    .color(Color::RED)
+            );
+            y += 30.0;
+        }
+    }
+    fn save_score(&mut self) {
+        let mode = if self.solo { "solo" } else if self.draw_menu.user_type { "multi" } else { "join" };
+        self.leaderboard.record(leaderboard::Record {
+            name: self.name.clone(),
+            outcome: self.game.outcome,
+            time: self.timer.get_time(),
+            penalty: self.timer.penalty,
+            seed: self.game.seed,
+            width: self.game.width(),
+            height: self.game.height(),
+            algorithm: if self.game.seed.is_some() { self.algorithm.to_string() } else { "-".to_string() },
+            mode: mode.to_string(),
+        });
+    }
 }
 
 enum Direction {
@@ -221,13 +638,13 @@ enum Direction {
 }
 
 impl Direction {
-    pub fn inverse(&self) -> Self {
+    pub fn to_core(&self) -> Option<maze_core::Direction> {
         match *self {
-            Direction::Up => Direction::Down,
-            Direction::Down => Direction::Up,
-            Direction::Left => Direction::Right,
-            Direction::Right => Direction::Left,
-            Direction::Return => Direction::Return,
+            Direction::Up => Some(maze_core::Direction::Up),
+            Direction::Down => Some(maze_core::Direction::Down),
+            Direction::Left => Some(maze_core::Direction::Left),
+            Direction::Right => Some(maze_core::Direction::Right),
+            Direction::Return => None,
         }
     }
 
@@ -242,22 +659,40 @@ impl Direction {
         }
     }
 }
-struct timer{
+// mm:ss.mmm
+fn format_time(secs: f32) -> String {
+    let millis = (secs.max(0.0) * 1000.0) as u64;
+    format!("{:02}:{:02}.{:03}", millis / 60_000, millis / 1000 % 60, millis % 1000)
+}
+
+// 실제 흐른 시간(ggez delta)으로 간다. 멈춘 동안은 기록에 들어가지 않는다
+struct Timer{
     time: f32,
+    penalty: f32,
+    paused: bool,
+    splits: Vec<f32>,
 }
-impl timer{
+impl Timer{
     pub fn new() -> Self{
-        timer{time: 0.0}
+        Timer{time: 0.0, penalty: 0.0, paused: false, splits: Vec::new()}
     }
     fn update(&mut self, dt: f32){
-        self.time += dt;
-
+        if !self.paused {
+            self.time += dt;
+        }
+    }
+    fn split(&mut self){
+        self.splits.push(self.time);
+    }
+    fn add_penalty(&mut self, secs: f32){
+        self.time += secs;
+        self.penalty += secs;
     }
     fn get_time(&self) -> f32{
         self.time
     }
     fn draw(&self, canvas: &mut graphics::Canvas){
-        let text = graphics::Text::new(format!("{}", self.time));
+        let text = graphics::Text::new(format_time(self.time));
         let coord = [0.0, 0.0] ;
         canvas.draw(
             &text,
@@ -265,6 +700,22 @@ impl timer{
                 .dest(coord)
                 .color(graphics::Color::WHITE),
         );
+        let mut y = 80.0;
+        let mut last = 0.0;
+        for (i, &split) in self.splits.iter().enumerate() {
+            let text = graphics::Text::new(format!("split {} {} (+{})", i + 1, format_time(split), format_time(split - last)));
+            canvas.draw(&text, graphics::DrawParam::new().dest([0.0, y]).color(Color::WHITE));
+            last = split;
+            y += 20.0;
+        }
+        if self.paused {
+            canvas.draw(
+                &graphics::Text::new("PAUSED (P)"),
+                graphics::DrawParam::new()
+                    .dest([900.0, 580.0])
+                    .color(Color::RED),
+            );
+        }
     }
 }
 
@@ -275,19 +726,141 @@ struct Menu{
     in_menu: bool,
     solo: bool,
     user_type: bool,
+    seed_input: String,
+    room_input: String,
+    server_input: String,
+    players: u8,
+    typing: bool,
+    algorithm: usize,
+    size: usize,
+    size_input: String,
+    difficulty: usize,
+    // mazegame.cfg 에 bombs, doors, enemies 를 적었으면 난이도 대신 이것
+    bomb_count: Option<usize>,
+    door_count: Option<usize>,
+    enemy_kinds: Option<Vec<Behavior>>,
+    // 어두운 미로에서 보이는 거리. 멀티에서는 서버 설정을 따른다
+    sight: u8,
+    board: Option<usize>,
 }
 impl Menu{
     pub fn new(select:i32, list: Vec<String>) -> Self{
-        Menu{select, pos: [910.0, 500.0], list, in_menu: true, solo: true, user_type: false}
+        Menu{select, pos: [910.0, 500.0], list, in_menu: true, solo: true, user_type: false, seed_input: String::new(), room_input: String::new(), server_input: String::new(), players: 2, typing: false, algorithm: 0, size: 1, size_input: String::new(), difficulty: 1, bomb_count: None, door_count: None, enemy_kinds: None, sight: 0, board: None}
+    }
+    // 비워 두면 무작위. 숫자가 아니거나 u64 를 넘으면 무작위로 바꾸지 않고 알린다
+    fn seed(&self) -> Result<Option<u64>, String> {
+        if self.seed_input.is_empty() {
+            return Ok(None);
+        }
+        self.seed_input.parse().map(Some).map_err(|_| format!("Seed must be a number from 0 to {}", u64::MAX))
+    }
+    // "host" 만 적으면 기본 포트를 붙인다
+    fn server(&self) -> String {
+        let server = self.server_input.trim();
+        if server.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok()) {
+            server.to_string()
+        } else {
+            format!("{}:{}", server, protocol::DEFAULT_PORT)
+        }
+    }
+    fn join(&mut self) {
+        self.in_menu = false;
+        self.solo = false;
+        self.user_type = false;
+    }
+    // custom 크기는 "40x25" 처럼 입력한다. 잘못된 입력이면 medium 으로 만든다
+    fn size(&self) -> (usize, usize) {
+        if let Some(&(_, width, height)) = SIZES.get(self.size) {
+            return (width, height);
+        }
+        match self.size_input.split_once('x') {
+            Some((w, h)) => match (w.parse::<usize>(), h.parse::<usize>()) {
+                (Ok(w), Ok(h)) => (w.clamp(MIN_SIZE, MAX_SIZE), h.clamp(MIN_SIZE, MAX_SIZE)),
+                _ => (SIZES[1].1, SIZES[1].2),
+            },
+            None => (SIZES[1].1, SIZES[1].2),
+        }
+    }
+    fn bombs(&self) -> usize {
+        let (width, height) = self.size();
+        self.bomb_count.unwrap_or_else(|| DIFFICULTIES[self.difficulty].bombs(width, height))
+    }
+    fn doors(&self) -> usize {
+        self.door_count.unwrap_or_else(|| DIFFICULTIES[self.difficulty].doors())
+    }
+    fn enemies(&self) -> Vec<Behavior> {
+        self.enemy_kinds.clone().unwrap_or_else(|| DIFFICULTIES[self.difficulty].enemies().to_vec())
+    }
+    fn set_size(&mut self, width: usize, height: usize) {
+        match SIZES.iter().position(|&(_, w, h)| (w, h) == (width, height)) {
+            Some(index) => self.size = index,
+            None => {
+                self.size = SIZES.len();
+                self.size_input = format!("{}x{}", width, height);
+            }
+        }
+    }
+    fn input(&mut self) -> Option<&mut String> {
+        match self.list[self.select as usize].as_str() {
+            "Join" => Some(&mut self.server_input),
+            "Room" => Some(&mut self.room_input),
+            "Seed" => Some(&mut self.seed_input),
+            "Size" => Some(&mut self.size_input),
+            _ => None,
+        }
+    }
+    fn type_char(&mut self, ch: char) {
+        if !self.typing {
+            return;
+        }
+        let label = self.list[self.select as usize].clone();
+        if let Some(input) = self.input() {
+            let ok = match label.as_str() {
+                "Room" => !ch.is_control() && input.chars().count() < protocol::MAX_NAME,
+                "Join" => input.len() < 64 && (ch.is_ascii_alphanumeric() || ".:-[]".contains(ch)),
+                "Size" => input.len() < 20 && (ch.is_ascii_digit() || (ch == 'x' && !input.contains('x'))),
+                _ => input.len() < 20 && ch.is_ascii_digit(),
+            };
+            if ok {
+                input.push(ch);
+            }
+        }
     }
     fn draw(&self, canvas: &mut graphics::Canvas){
-        if !self.in_menu{
+        if !self.in_menu || self.board.is_some(){
             return;
         }
         let x = 950.0;
         let mut y = 500.0;
-        for i in 0..4{
-            let text = graphics::Text::new(self.list[i].clone());
+        for i in 0..self.list.len(){
+            let cursor = if self.typing && self.select as usize == i { "_" } else { "" };
+            let label = if self.list[i] == "Join" {
+                format!("Join: {}{}", self.server_input, cursor)
+            } else if self.list[i] == "Room" {
+                format!("Room: {}{}", self.room_input, cursor)
+            } else if self.list[i] == "Players" {
+                format!("Players: {}", self.players)
+            } else if self.list[i] == "Seed" {
+                let input = if self.seed_input.is_empty() && cursor.is_empty() { "random" } else { &self.seed_input };
+                format!("Seed: {}{}", input, cursor)
+            } else if self.list[i] == "Size" {
+                match SIZES.get(self.size) {
+                    Some((name, w, h)) => format!("Size: {} ({}x{})", name, w, h),
+                    None => format!("Size: custom {}{}", self.size_input, cursor),
+                }
+            } else if self.list[i] == "Difficulty" {
+                format!("Difficulty: {} ({} bombs, {} doors, {} enemies)", DIFFICULTIES[self.difficulty].name(), self.bombs(), self.doors(), self.enemies().len())
+            } else if self.list[i] == "Sight" {
+                match self.sight {
+                    0 => "Sight: full".to_string(),
+                    sight => format!("Sight: {} cells", sight),
+                }
+            } else if self.list[i] == "Algorithm" {
+                format!("Algorithm: {}", generator::ALGORITHMS[self.algorithm])
+            } else {
+                self.list[i].clone()
+            };
+            let text = graphics::Text::new(label);
             let coord = [x,y] ;
             canvas.draw(
                 &text,
@@ -309,10 +882,49 @@ impl Menu{
                 .color(Color::BLACK)
         );
     }
+    // 카테고리 하나의 순위. 좌우 키로 카테고리를 바꾼다
+    fn draw_board(&self, canvas: &mut graphics::Canvas, leaderboard: &leaderboard::Leaderboard){
+        let board = match self.board {
+            Some(board) if self.in_menu => board,
+            _ => return,
+        };
+        let categories = leaderboard.categories();
+        let mut lines = vec!["Leaderboard (Left/Right, Esc to close)".to_string()];
+        match categories.get(board) {
+            Some(category) => {
+                lines.push(format!("< {} > {}/{}", category, board + 1, categories.len()));
+                for (rank, record) in leaderboard.top(category).iter().enumerate() {
+                    let seed = record.seed.map_or("-".to_string(), |seed| seed.to_string());
+                    let outcome = if record.outcome == Outcome::Escaped { "escaped" } else { "bombed" };
+                    lines.push(format!("{:2}. {:<12} {:>9}  {}  seed {}", rank + 1, record.name, format_time(record.time), outcome, seed));
+                }
+            }
+            None => lines.push("no scores yet".to_string()),
+        }
+        let mut y = 400.0;
+        for line in lines {
+            canvas.draw(
+                &graphics::Text::new(line),
+                graphics::DrawParam::new()
+                    .dest([750.0, y])
+                    .color(Color::BLACK)
+            );
+            y += 30.0;
+        }
+    }
+    fn go_board(&mut self, key: KeyCode, categories: usize) {
+        let board = self.board.unwrap_or(0);
+        match key {
+            KeyCode::Left if categories > 0 => self.board = Some((board + categories - 1) % categories),
+            KeyCode::Right if categories > 0 => self.board = Some((board + 1) % categories),
+            KeyCode::Return | KeyCode::Escape => self.board = None,
+            _ => {}
+        }
+    }
     fn go(&mut self, dir: Direction) {
         match dir {
-            Direction::Up => if self.pos[1] != 500.0 {self.pos[1] -= 30.0;self.select -= 1;},
-            Direction::Down => if self.pos[1] != 590.0 {self.pos[1] += 30.0;self.select+=1; },
+            Direction::Up if self.pos[1] != 500.0 => {self.pos[1] -= 30.0;self.select -= 1;},
+            Direction::Down if self.pos[1] != 500.0 + 30.0 * (self.list.len() - 1) as f32 => {self.pos[1] += 30.0;self.select+=1; },
             Direction::Return => Menu::action(self),
             _ => {}
         }
@@ -320,16 +932,31 @@ impl Menu{
     fn action(&mut self){
         if self.select == 0{
             self.in_menu = false;
+            self.solo = true;
+            self.user_type = false;
         }else if self.select == 1 {
             self.in_menu = false;
             self.solo = false;
             self.user_type = true;
-            //add multi action
-        }else if self.select == 2 {
-            self.in_menu = false;
-            self.solo = false;
-            //add join action
-        }else if self.select == 3 {
+        }else if self.select == 2 || self.select == 3 || self.select == 5 {
+            self.typing = true;
+        }else if self.select == 4 {
+            self.players = if self.players >= 8 { 2 } else { self.players + 1 };
+        }else if self.select == 6 {
+            self.algorithm = (self.algorithm + 1) % generator::ALGORITHMS.len();
+        }else if self.select == 7 {
+            self.size = (self.size + 1) % (SIZES.len() + 1);
+            self.typing = self.size == SIZES.len();
+        }else if self.select == 8 {
+            self.difficulty = (self.difficulty + 1) % DIFFICULTIES.len();
+            self.bomb_count = None;
+            self.door_count = None;
+            self.enemy_kinds = None;
+        }else if self.select == 9 {
+            self.sight = SIGHTS.iter().copied().find(|&sight| sight > self.sight).unwrap_or(0);
+        }else if self.select == 10 {
+            self.board = Some(0);
+        }else if self.select == 11 {
             std::process::exit(0);
         }
     }
@@ -344,42 +971,13 @@ impl Exit {
     pub fn new(pos: GridPosition) -> Self {
         Exit { pos, can: false }
     }
-    fn draw(&self, canvas: &mut graphics::Canvas, solo:bool) {
+    fn draw(&self, canvas: &mut graphics::Canvas, size: (usize, usize)) {
         if self.can{
-            if solo{
-                canvas.draw(
-                    &graphics::Quad,
-                    graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            ((self.pos.x * 40) + 350) as f32,
-                            (self.pos.y * 40)  as f32,
-                            40.0,
-                            40.0,
-                        ))
-                        .color(Color::YELLOW), );
-            }else{
-                canvas.draw(
-                    &graphics::Quad,
-                    graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            ((self.pos.x * 30)) as f32,
-                            (self.pos.y * 40)  as f32,
-                            30.0,
-                            40.0,
-                        ))
-                        .color(Color::YELLOW), );
-                canvas.draw(
-                    &graphics::Quad,
-                    graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            ((self.pos.x * 30) + 1000) as f32,
-                            (self.pos.y * 40)  as f32,
-                            30.0,
-                            40.0,
-                        ))
-                        .color(Color::YELLOW), );
-            }
-
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(View::new(size).rect(self.pos.x, self.pos.y))
+                    .color(Color::YELLOW), );
         }
     }
     fn update(&mut self, can : bool){
@@ -387,56 +985,175 @@ impl Exit {
     }
 }
 
+// 플레이어 앞의 최단 경로 몇 칸. 그 길을 따라 걸으면 지나온 칸은 지우고, 벗어나면 전부 지운다
+struct Hint {
+    path: Vec<GridPosition>,
+    left: u32,
+}
+
+impl Hint {
+    pub fn new() -> Self {
+        Hint { path: Vec::new(), left: HINTS }
+    }
+    fn show(&mut self, path: &[(usize, usize)]) {
+        self.path = path.iter().skip(1).take(HINT_STEPS).map(|&pos| pos.into()).collect();
+        self.left -= 1;
+    }
+    fn moved(&mut self, pos: &GridPosition) {
+        match self.path.iter().position(|p| p.x == pos.x && p.y == pos.y) {
+            Some(index) => { self.path.drain(..=index); },
+            None => self.path.clear(),
+        }
+    }
+    fn draw(&self, canvas: &mut graphics::Canvas, size: (usize, usize)) {
+        let view = View::new(size);
+        for pos in &self.path {
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(view.rect(pos.x, pos.y))
+                    .color(Color::new(0.3, 0.6, 1.0, 0.6)), );
+        }
+    }
+}
+
+// 어두운 미로. sight 칸 안에서 벽에 가리지 않은 곳만 보이고, 한 번 본 칸은 흐리게 남는다
+struct Fog {
+    sight: u8,
+    visible: Vec<Vec<bool>>,
+    explored: Vec<Vec<bool>>,
+}
+
+impl Fog {
+    pub fn new(sight: u8) -> Self {
+        Fog { sight, visible: Vec::new(), explored: Vec::new() }
+    }
+    fn reset(&mut self) {
+        self.visible.clear();
+        self.explored.clear();
+    }
+    // 벽이 터지거나 문이 열려도 맞도록 매 프레임 다시 본다
+    fn look(&mut self, map: &[Vec<char>], pos: (usize, usize)) {
+        if self.sight == 0 {
+            return;
+        }
+        self.visible = fov::visible(map, pos, self.sight as usize);
+        if self.explored.len() != map.len() {
+            self.explored = vec![vec![false; map[0].len()]; map.len()];
+        }
+        for (explored, &visible) in self.explored.iter_mut().flatten().zip(self.visible.iter().flatten()) {
+            *explored |= visible;
+        }
+    }
+    // 미로처럼 칸마다 픽셀 하나인 그림 하나로 덮는다
+    fn draw(&self, ctx: &Context, canvas: &mut graphics::Canvas, size: (usize, usize)) {
+        if self.sight == 0 || self.visible.len() != size.0 {
+            return;
+        }
+        let mut pixels = vec![0u8; size.0 * size.1 * 4];
+        for x in 0..size.0 {
+            for y in 0..size.1 {
+                if self.visible[x][y] {
+                    continue;
+                }
+                let alpha = if self.explored[x][y] { 153 } else { 255 };
+                let i = (y * size.0 + x) * 4;
+                pixels[i..i + 4].copy_from_slice(&[51, 51, 51, alpha]);
+            }
+        }
+        let image = graphics::Image::from_pixels(ctx, &pixels, ImageFormat::Rgba8Unorm, size.0 as u32, size.1 as u32);
+        draw_cells(canvas, &image, &View::new(size));
+    }
+}
+
+struct Reconnect {
+    since: Instant,
+    next_try: Instant,
+}
+
+// 멀티 판의 한 사람. 결과 화면은 탈출한 순서, 그다음 폭탄에 맞은 사람 순으로 줄 세운다
+struct Racer {
+    player: Player,
+    outcome: Outcome,
+    // 연결이 끊겨 돌아오기를 기다리는 중
+    away: bool,
+    left: bool,
+    // 결과 화면에서 한 판 더 하자고 했다
+    rematch: bool,
+}
+
+impl Racer {
+    pub fn new(number: u8) -> Self {
+        let color = RACER_COLORS[number as usize % RACER_COLORS.len()];
+        let mut player = Player::new(GridPosition::new(0, 0), color);
+        player.update(true);
+        Racer { player, outcome: Outcome::Playing, away: false, left: false, rematch: false }
+    }
+}
+
 struct Bomb {
     pos: GridPosition,
+    // 터지기까지 남은 초
     timer: f32,
+    radius: u8,
+    exploded: bool,
+    // 터진 뒤 폭발 그림이 남은 초
+    blast: f32,
     can:bool,
 }
 
 impl Bomb {
-    pub fn new(pos: GridPosition) -> Self {
-        Bomb { pos, timer: 0.0 , can: false}
+    pub fn new(pos: GridPosition, rules: BombRules) -> Self {
+        Bomb { pos, timer: rules.fuse_ms as f32 / 1000.0, radius: rules.radius, exploded: false, blast: 0.0, can: false}
     }
-    fn draw(&self, canvas: &mut graphics::Canvas, solo:bool) {
-        if self.can{
-            if solo{
-                canvas.draw(
-                    &graphics::Quad,
-                    graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            ((self.pos.x*40) + 350) as f32,
-                            (self.pos.y * 40 ) as f32,
-                            40.0,
-                            40.0,
-                        ))
-                        .color(Color::RED), );
-            }else{
-                canvas.draw(
-                    &graphics::Quad,
-                    graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            (self.pos.x * 30) as f32,
-                            (self.pos.y * 40 ) as f32,
-                            30.0,
-                            40.0,
-                        ))
-                        .color(Color::RED), );
-                canvas.draw(
-                    &graphics::Quad,
-                    graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            ((self.pos.x * 30) + 1000) as f32,
-                            (self.pos.y * 40) as f32,
-                            30.0,
-                            40.0,
-                        ))
-                        .color(Color::RED), );
+    fn draw(&self, canvas: &mut graphics::Canvas, size: (usize, usize)) {
+        if !self.can {
+            return;
+        }
+        let view = View::new(size);
+        if !self.exploded {
+            let rect = view.rect(self.pos.x, self.pos.y);
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(rect)
+                    .color(Color::RED), );
+            canvas.draw(
+                &graphics::Text::new(format!("{:.1}", self.timer)),
+                graphics::DrawParam::new()
+                    .dest([rect.x, rect.y - 16.0])
+                    .color(Color::RED),
+            );
+        }
+        // 폭발 범위가 점점 옅어진다
+        if self.blast > 0.0 {
+            let r = self.radius as i16;
+            let color = Color::new(1.0, 0.5, 0.0, self.blast / BLAST_TIME);
+            for x in (self.pos.x - r).max(0)..=(self.pos.x + r).min(size.0 as i16 - 1) {
+                for y in (self.pos.y - r).max(0)..=(self.pos.y + r).min(size.1 as i16 - 1) {
+                    canvas.draw(&graphics::Quad, graphics::DrawParam::new().dest_rect(view.rect(x, y)).color(color));
+                }
             }
         }
-
     }
-    fn boom() {
-        //TODO
+    // 불이 다 타면 true
+    fn burn(&mut self, dt: f32) -> bool {
+        if self.exploded {
+            return false;
+        }
+        self.timer = (self.timer - dt).max(0.0);
+        self.timer == 0.0
+    }
+    fn boom(&mut self) {
+        self.exploded = true;
+        self.timer = 0.0;
+        self.blast = BLAST_TIME;
+    }
+    fn animate(&mut self, dt: f32) {
+        self.blast = (self.blast - dt).max(0.0);
+    }
+    fn blasting(&self) -> bool {
+        self.blast > 0.0
     }
     fn update(&mut self, can : bool){
         self.can = can;
@@ -445,74 +1162,21 @@ impl Bomb {
 
 struct Player {
     pos: GridPosition,
+    color: Color,
     can:bool,
 }
 
 impl Player {
-    pub fn new(pos: GridPosition) -> Self {
-        Player { pos, can: false}
+    pub fn new(pos: GridPosition, color: Color) -> Self {
+        Player { pos, color, can: false}
     }
-    fn draw(&self, canvas: &mut graphics::Canvas, solo:bool,multi:bool) {
+    fn draw(&self, canvas: &mut graphics::Canvas, size: (usize, usize)) {
         if self.can{
-            if solo{
-                canvas.draw(
-                    &graphics::Quad,
-                    graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            ((self.pos.x * 40) + 350) as f32,
-                            (self.pos.y * 40)  as f32,
-                            40.0,
-                            40.0,
-                        ))
-                        .color(Color::GREEN), );
-            }else if multi{
-                canvas.draw(
-                    &graphics::Quad,
-                    graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            ((self.pos.x * 30) +1000) as f32,
-                            (self.pos.y * 40) as f32,
-                            30.0,
-                            40.0,
-                        ))
-                        .color(Color::GREEN), );
-            }else{
-                canvas.draw(
-                    &graphics::Quad,
-                    graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            (self.pos.x * 30) as f32,
-                            (self.pos.y * 40)  as f32,
-                            30.0,
-                            40.0,
-                        ))
-                        .color(Color::GREEN), );
-            }
-
-        }
-
-    }
-    fn go(&mut self, dir: Direction, solo:bool, map:Vec<Vec<char>>) {
-        if solo{
-            match dir {
-
-                Direction::Up => if self.pos.y > 0 && map[self.pos.x as usize][self.pos.y as usize-1] != '#' && self.pos.y<= 30 {self.pos.y -= 1},
-                Direction::Down => if self.pos.y < 29  && map[self.pos.x as usize][self.pos.y as usize+1] != '#' && self.pos.y<= 30 {self.pos.y += 1},
-                Direction::Left => if self.pos.x > 0 && map[self.pos.x as usize -1][self.pos.y as usize] != '#' && self.pos.x<= 30 {self.pos.x -= 1},
-                Direction::Right => if self.pos.x < 29 && map[self.pos.x as usize +1][self.pos.y as usize] != '#' && self.pos.x<= 30 {self.pos.x += 1},
-
-                _ => {}
-            }
-        }else{
-            match dir {
-
-                Direction::Up => if self.pos.y > 0 && map[self.pos.x as usize][self.pos.y as usize-1] != '#' && self.pos.y<= 30 {self.pos.y -= 1},
-                Direction::Down => if self.pos.y < 29 && map[self.pos.x as usize][self.pos.y as usize+1] != '#' && self.pos.y<= 30 {self.pos.y += 1},
-                Direction::Left => if self.pos.x > 0 && map[self.pos.x as usize -1][self.pos.y as usize] != '#' && self.pos.x<= 30 {self.pos.x -= 1},
-                Direction::Right => if self.pos.x < 29 && map[self.pos.x as usize +1][self.pos.y as usize] != '#' && self.pos.x<= 30 {self.pos.x += 1},
-
-                _ => {}
-            }
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(View::new(size).rect(self.pos.x, self.pos.y))
+                    .color(self.color), );
         }
 
     }
@@ -527,23 +1191,24 @@ impl Player {
 
 }
 
+// 미로는 칸 하나를 픽셀 하나로 한 그림으로 그린다. 그림은 미로가 바뀔 때 (문, 열쇠, 폭발) 만 다시 만든다
 struct Wall {
-    pos: GridPosition,
     can:bool,
+    drawn: Vec<Vec<char>>,
+    image: Option<graphics::Image>,
+    // 바닥에 남은 열쇠 (x, y, 번호). 칸 가운데 작게 따로 그린다
+    keys: Vec<(usize, usize, usize)>,
 }
 
+#[derive(PartialEq)]
 struct GridPosition {
     x: i16,
     y: i16,
 }
 
-struct Entity<'a> {
-    pos: &'a GridPosition,
-}
-
-impl Into<GridPosition> for (i16, i16) {
-    fn into(self) -> GridPosition {
-        GridPosition { x: self.0, y: self.1 }
+impl From<(usize, usize)> for GridPosition {
+    fn from(pos: (usize, usize)) -> Self {
+        GridPosition::new(pos.0 as i16, pos.1 as i16)
     }
 }
 
@@ -553,226 +1218,208 @@ impl GridPosition {
     }
 }
 
+// 화면 가운데의 미로 영역. 칸 크기는 미로 크기에 맞춰 영역을 꽉 채우도록 정한다
+// (30x30 이면 40x40). 멀티도 모두 같은 미로를 한 화면에서 본다
+struct View {
+    x: f32,
+    y: f32,
+    cell_w: f32,
+    cell_h: f32,
+}
+
+impl View {
+    fn new(size: (usize, usize)) -> Self {
+        View { x: 350.0, y: 0.0, cell_w: 1200.0 / size.0 as f32, cell_h: 1200.0 / size.1 as f32 }
+    }
+    fn rect(&self, x: i16, y: i16) -> Rect {
+        Rect::new(self.x + x as f32 * self.cell_w, self.y + y as f32 * self.cell_h, self.cell_w, self.cell_h)
+    }
+}
+
 impl Wall {
-    pub fn new(pos: GridPosition,can:bool) -> Self {
-        Wall { pos, can }
+    pub fn new(can:bool) -> Self {
+        Wall { can, drawn: Vec::new(), image: None, keys: Vec::new() }
     }
     fn update(&mut self, can : bool){
         self.can = can;
     }
-    fn draw(&self, canvas: &mut graphics::Canvas, map: &Vec<Vec<char>>,solo:bool) {
-        if self.can{
-            if solo{
-                for i in 0..MAP_SIZE {
-                    for j in 0..MAP_SIZE {
-                        if map[i][j] == '#' {
-                            canvas.draw(
-                                &graphics::Quad,
-                                graphics::DrawParam::new()
-                                    .dest_rect(Rect::new(
-                                        ((i * 40) + 350) as f32,
-                                        (j * 40) as f32,
-                                        40.0,
-                                        40.0,
-                                    ))
-                                    .color(Color::BLACK), );
-                        }
-
-                    }
-                }
-                canvas.draw(
-                    &graphics::Quad,
-                    graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            0.0 as f32,
-                            0.0  as f32,
-                            350.0,
-                            1200.0,
-                        ))
-                        .color(Color::BLACK), );
-                canvas.draw(
-                    &graphics::Quad,
-                    graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            1550.0 as f32,
-                            0.0  as f32,
-                            350.0,
-                            1200.0,
-                        ))
-                        .color(Color::BLACK), );
-            }else{
-                for i in 0..MAP_SIZE {
-                    for j in 0..MAP_SIZE {
-                        if map[i][j] == '#' {
-                            canvas.draw(
-                                &graphics::Quad,
-                                graphics::DrawParam::new()
-                                    .dest_rect(Rect::new(
-                                        (i * 30) as f32,
-                                        (j * 40) as f32,
-                                        30.0,
-                                        40.0,
-                                    ))
-                                    .color(Color::BLACK), );
-                        }
-
+    fn redraw(&mut self, ctx: &Context, map: &[Vec<char>]) {
+        let (width, height) = (map.len(), map[0].len());
+        let mut pixels = vec![0u8; width * height * 4];
+        self.keys.clear();
+        for (x, column) in map.iter().enumerate() {
+            for (y, &cell) in column.iter().enumerate() {
+                let color = if cell == '#' {
+                    Color::BLACK
+                } else if let Some(door) = solver::door_index(cell) {
+                    KEY_COLORS[door]
+                } else {
+                    if let Some(key) = solver::key_index(cell) {
+                        self.keys.push((x, y, key));
                     }
-                }
+                    continue;
+                };
+                let (r, g, b, a) = color.to_rgba();
+                let i = (y * width + x) * 4;
+                pixels[i..i + 4].copy_from_slice(&[r, g, b, a]);
+            }
+        }
+        self.image = Some(graphics::Image::from_pixels(ctx, &pixels, ImageFormat::Rgba8Unorm, width as u32, height as u32));
+        self.drawn = map.to_vec();
+    }
+    fn draw(&mut self, ctx: &Context, canvas: &mut graphics::Canvas, map: &[Vec<char>]) {
+        if self.can{
+            if self.image.is_none() || self.drawn != map {
+                self.redraw(ctx, map);
+            }
+            let view = View::new((map.len(), map[0].len()));
+            if let Some(image) = &self.image {
+                draw_cells(canvas, image, &view);
+            }
+            for &(x, y, key) in &self.keys {
+                // 열쇠는 칸 가운데 작게
+                let rect = view.rect(x as i16, y as i16);
+                let key_rect = Rect::new(rect.x + rect.w / 3.0, rect.y + rect.h / 3.0, rect.w / 3.0, rect.h / 3.0);
                 canvas.draw(
                     &graphics::Quad,
                     graphics::DrawParam::new()
-                        .dest_rect(Rect::new(
-                            900.0 as f32,
-                            0.0  as f32,
-                            100.0,
-                            1200.0,
-                        ))
-                        .color(Color::BLACK), );
-                for i in 0..MAP_SIZE {
-                    for j in 0..MAP_SIZE {
-                        if map[i][j] == '#' {
-                            canvas.draw(
-                                &graphics::Quad,
-                                graphics::DrawParam::new()
-                                    .dest_rect(Rect::new(
-                                        ((i * 30) + 1000) as f32,
-                                        (j * 40) as f32,
-                                        30.0,
-                                        40.0,
-                                    ))
-                                    .color(Color::BLACK), );
-                        }
-
-                    }
-                }
+                        .dest_rect(key_rect)
+                        .color(KEY_COLORS[key]), );
             }
+            // 미로 양옆
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(Rect::new(
+                        0.0,
+                        0.0,
+                        350.0,
+                        1200.0,
+                    ))
+                    .color(Color::BLACK), );
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(Rect::new(
+                        1550.0,
+                        0.0,
+                        350.0,
+                        1200.0,
+                    ))
+                    .color(Color::BLACK), );
 
         }
     }
 }
 
+// 칸마다 픽셀 하나인 그림을 미로 영역에 늘려 그린다. 칸 경계가 번지지 않도록 가장 가까운 픽셀을 쓴다
+fn draw_cells(canvas: &mut graphics::Canvas, image: &graphics::Image, view: &View) {
+    canvas.set_sampler(Sampler::nearest_clamp());
+    canvas.draw(image, graphics::DrawParam::new().dest([view.x, view.y]).scale([view.cell_w, view.cell_h]));
+    canvas.set_default_sampler();
+}
+
 impl EventHandler for MyGame {
-    fn update(&mut self, _ctx: &mut Context) -> GameResult {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+            self.discovery.update();
 
             if !self.draw_menu.in_menu {
-                self.timer.update(0.01);
                 self.solo = self.draw_menu.solo;
-                if !self.solo {
-                    if self.first && self.draw_menu.user_type{
-                        self.first = false;
-                        println!("asd");
-                        self.client_connect("127.0.0.1:8088",true);
-                        println!("asd");
-                        self.mulit_player.update(true);
-                        let maze_flat = self.map.iter().flatten().map(|&c| c as u8).collect::<Vec<u8>>();
-                        println!("{:?}", maze_flat.len());
-                        unsafe {
-                            if let Some(server_socket) = &mut self.socket_client {
-                                // println!("{:?}", player_pos_bytes);
-                                server_socket
-                                    .write_all(&maze_flat)
-                                    .map_err(|e| {
-                                        io::Error::new(
-                                            ErrorKind::Other,
-                                            format!("Failed to send player position to server: {}", e),
-                                        )
-                                    })?;
-                            }
-                        }
+                // 미로를 만드는 Solo 와 Multi 만 seed 를 쓴다
+                if self.first && (self.solo || self.draw_menu.user_type) {
+                    if let Err(e) = self.draw_menu.seed() {
+                        self.notice = e;
+                        self.back_to_menu();
+                        return Ok(());
                     }
+                }
+                if self.first && self.solo {
+                    let seed = self.draw_menu.seed().ok().flatten().unwrap_or_else(random_seed);
+                    self.notice.clear();
+                    self.start_round(seed);
+                    self.first = false;
+                }
+                if self.generating.is_some() {
+                    self.collect_round();
+                    if self.generating.is_some() {
+                        return Ok(());
+                    }
+                }
+                let dt = ctx.time.delta().as_secs_f32();
+                if self.in_the_dark() {
+                    self.fog.look(&self.game.map, self.game.player);
+                }
+                if self.game.outcome == Outcome::Playing && self.results.is_none() && !self.locked {
+                    self.timer.update(dt);
+                }
+                // 혼자 할 때는 직접 터뜨리고, 멀티에서는 서버의 Exploded 를 기다린다
+                let running = if self.solo { self.game.outcome == Outcome::Playing } else { self.results.is_none() };
+                let burning = running && !self.locked && !self.waiting && !self.timer.paused;
+                for bomb in &mut self.bombs {
+                    bomb.animate(dt);
+                    if burning && bomb.burn(dt) && self.solo {
+                        self.game.explode((bomb.pos.x as usize, bomb.pos.y as usize), bomb.radius);
+                        bomb.boom();
+                    }
+                }
+                if burning && self.solo {
+                    self.hunt(dt);
+                }
+                if !self.solo {
                     if self.first {
                         self.first = false;
-                        println!("asd");
-                        self.client_connect("127.0.0.1:8088",true);
-                        println!("asd");
-                        self.mulit_player.update(true);
-                        let mut buffer = [0u8; 900];
-                        unsafe {
-                            if let Some(server_socket) = &mut self.socket_client {
-                                server_socket.read(&mut buffer).map_err(|e| {
-                                    io::Error::new(
-                                        ErrorKind::Other,
-                                        format!("Failed to receive data from server: {}", e),
-                                    )
-                                })?;
-                                // println!("{:?}", buffer);
-                            }
-                        }
-                        let maze = buffer.chunks(30 as usize)
-                            .map(|chunk| chunk.iter().map(|&b| b as char).collect())
-                            .collect::<Vec<Vec<char>>>();
-                        println!("{:?}", maze);
-                        self.map = maze;
-                        let mut buffer = [0u8; 4];
-                        unsafe {
-                            if let Some(server_socket) = &mut self.socket_client {
-                                server_socket.read(&mut buffer).map_err(|e| {
-                                    io::Error::new(
-                                        ErrorKind::Other,
-                                        format!("Failed to receive data from server: {}", e),
-                                    )
-                                })?;
-                                // println!("{:?}", buffer);
-                            }
+                        self.me = None;
+                        self.token = None;
+                        self.notice.clear();
+                        let server = self.draw_menu.server();
+                        self.client_connect(&server,true);
+                        // 미로는 서버가 만든다. Multi 는 메뉴 설정으로 방을 만들고, Join 은 방 목록부터 본다
+                        if self.draw_menu.user_type {
+                            let (width, height) = self.draw_menu.size();
+                            self.algorithm = generator::ALGORITHMS[self.draw_menu.algorithm];
+                            self.waiting = true;
+                            self.send(Message::CreateRoom {
+                                room: self.draw_menu.room_input.clone(),
+                                players: self.draw_menu.players,
+                                seed: self.draw_menu.seed().ok().flatten().unwrap_or_else(random_seed),
+                                algorithm: self.algorithm.to_string(),
+                                width: width as u16,
+                                height: height as u16,
+                                bombs: self.draw_menu.bombs().min(u16::MAX as usize) as u16,
+                                doors: self.draw_menu.doors() as u8,
+                                enemies: self.draw_menu.enemies(),
+                            });
+                        } else {
+                            self.browsing = true;
+                            self.send(Message::ListRooms);
                         }
-                        self.player.pos.x = i16::from_be_bytes(buffer[0..2].try_into().unwrap());
-                        self.player.pos.y = i16::from_be_bytes(buffer[2..4].try_into().unwrap());
                     }
-
-
-                    let player_pos_bytes_x = self.player.pos.x.to_be_bytes();
-                    let player_pos_bytes_y = self.player.pos.y.to_be_bytes();
-                    let mut player_pos_bytes = [&player_pos_bytes_x[..], &player_pos_bytes_y[..]].concat();
-
-                    unsafe {
-                        if let Some(server_socket) = &mut self.socket_client {
-                            // println!("{:?}", player_pos_bytes);
-                            server_socket
-                                .write_all(&player_pos_bytes)
-                                .map_err(|e| {
-                                    io::Error::new(
-                                        ErrorKind::Other,
-                                        format!("Failed to send player position to server: {}", e),
-                                    )
-                                })?;
-                        }
+                    if self.socket_client.is_some() && self.heartbeat.elapsed() >= HEARTBEAT {
+                        self.heartbeat = Instant::now();
+                        self.send(Message::Ping { nonce: self.timer.get_time().to_bits() });
                     }
-
-                    let mut buffer = [0u8; 4];
-                    unsafe {
-                        if let Some(server_socket) = &mut self.socket_client {
-                            server_socket.read(&mut buffer).map_err(|e| {
-                                io::Error::new(
-                                    ErrorKind::Other,
-                                    format!("Failed to receive data from server: {}", e),
-                                )
-                            })?;
-                            // println!("{:?}", buffer);
-                        }
+                    self.receive();
+                    self.try_reconnect();
+                    if self.draw_menu.in_menu || self.browsing {
+                        return Ok(());
                     }
-                    self.mulit_player.pos.x = i16::from_be_bytes(buffer[0..2].try_into().unwrap());
-                    self.mulit_player.pos.y = i16::from_be_bytes(buffer[2..4].try_into().unwrap());
                 }
                 self.wall.update(true);
-                self.player.update(true);
+                self.player.update(!self.spectating);
                 self.exit.update(true);
-                self.bomb.update(true);
+                for bomb in &mut self.bombs {
+                    bomb.update(true);
+                }
                 self.end_game();
                 if self.end {
-                    let mut file = File::create("score.txt");
-                    file?.write_all(self.timer.time.to_string().as_bytes()).expect("Failed to write to file");
-
-
-                    if !self.solo{
-                        self.client_connect("",false);
+                    if !self.saved {
+                        self.save_score();
+                        self.saved = true;
+                    }
+                    // 멀티는 다른 사람이 끝날 때까지 보다가 결과 화면에서 나간다. 혼자면 폭발 그림이 끝난 뒤 메뉴로
+                    if self.solo && !self.bombs.iter().any(Bomb::blasting) {
+                        self.back_to_menu();
                     }
-                    self.end = false;
-                    self.draw_menu.in_menu = true;
-                    self.first = true;
-                    self.wall.update(false);
-                    self.player.update(false);
-                    self.exit.update(false);
-                    self.bomb.update(false);
                 }
             }
 
@@ -784,21 +1431,239 @@ impl EventHandler for MyGame {
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         let mut canvas = graphics::Canvas::from_frame(ctx, Color::WHITE);
 
-        self.wall.draw(&mut canvas, &self.map, self.solo);
-        self.player.draw(&mut canvas, self.solo, false);
-        self.mulit_player.draw(&mut canvas, self.solo, true);
-        self.exit.draw(&mut canvas, self.solo);
-        self.bomb.draw(&mut canvas, self.solo);
+        let size = (self.game.width(), self.game.height());
+        self.wall.draw(ctx, &mut canvas, &self.game.map);
+        self.exit.draw(&mut canvas, size);
+        for bomb in &self.bombs {
+            bomb.draw(&mut canvas, size);
+        }
+        self.draw_enemies(&mut canvas, size);
+        if !self.draw_menu.in_menu {
+            for (&number, racer) in &self.racers {
+                if Some(number) != self.me && !racer.left {
+                    racer.player.draw(&mut canvas, size);
+                }
+            }
+            if self.in_the_dark() {
+                self.fog.draw(ctx, &mut canvas, size);
+            }
+            self.hint.draw(&mut canvas, size);
+        }
+        self.player.draw(&mut canvas, size);
+        // 멈춘 동안은 미로를 가려서 시계를 세워 두고 길을 찾을 수 없게 한다
+        if !self.draw_menu.in_menu && self.timer.paused {
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(Rect::new(350.0, 0.0, 1200.0, 1200.0))
+                    .color(Color::new(0.2, 0.2, 0.2, 1.0)), );
+        }
+        if let (false, Some(results)) = (self.draw_menu.in_menu, &self.results) {
+            self.draw_results(&mut canvas, results);
+        }
         self.draw_menu.draw(&mut canvas);
+        if self.draw_menu.in_menu && self.draw_menu.board.is_none() && self.draw_menu.list[self.draw_menu.select as usize] == "Join" {
+            self.draw_lan(&mut canvas);
+        }
+        self.draw_menu.draw_board(&mut canvas, &self.leaderboard);
         self.timer.draw(&mut canvas);
+        if let (false, Some(seed)) = (self.draw_menu.in_menu, self.game.seed) {
+            canvas.draw(
+                &graphics::Text::new(format!("seed {} ({})", seed, self.algorithm)),
+                graphics::DrawParam::new()
+                    .dest([0.0, 20.0])
+                    .color(Color::WHITE),
+            );
+        }
+        if !self.draw_menu.in_menu && self.browsing {
+            self.draw_rooms(&mut canvas);
+        }
+        if !self.notice.is_empty() && (self.draw_menu.in_menu || self.browsing) {
+            canvas.draw(
+                &graphics::Text::new(self.notice.as_str()),
+                graphics::DrawParam::new()
+                    .dest([750.0, 360.0])
+                    .color(Color::RED),
+            );
+        }
+        if !self.draw_menu.in_menu && self.generating.is_some() {
+            canvas.draw(
+                &graphics::Text::new("generating maze..."),
+                graphics::DrawParam::new()
+                    .dest([900.0, 580.0])
+                    .color(Color::RED),
+            );
+        }
+        if !self.draw_menu.in_menu && self.waiting {
+            canvas.draw(
+                &graphics::Text::new("waiting for other players... (Esc to leave)"),
+                graphics::DrawParam::new()
+                    .dest([900.0, 580.0])
+                    .color(Color::RED),
+            );
+        }
+        // 출발 신호는 미로 가운데 크게
+        let count = match self.countdown {
+            Some(0) if self.timer.get_time() < 1.0 => Some("GO!".to_string()),
+            Some(0) | None => None,
+            Some(seconds) => Some(seconds.to_string()),
+        };
+        if let (false, Some(count)) = (self.draw_menu.in_menu, count) {
+            let mut text = graphics::Text::new(count);
+            text.set_scale(120.0);
+            canvas.draw(
+                &text,
+                graphics::DrawParam::new()
+                    .dest([900.0, 520.0])
+                    .color(Color::RED),
+            );
+        }
+        if !self.draw_menu.in_menu && !self.solo && self.results.is_none() {
+            let mut lines = Vec::new();
+            if self.spectating {
+                lines.push("spectating (Esc to leave)".to_string());
+            }
+            if self.locked && self.countdown.is_none() {
+                lines.push("waiting for everyone to load the maze...".to_string());
+            }
+            if let Some(reconnect) = &self.reconnect {
+                let left = RECONNECT_WINDOW.saturating_sub(reconnect.since.elapsed()).as_secs();
+                lines.push(format!("connection lost, reconnecting... ({}s, Esc to give up)", left));
+            }
+            for (&number, racer) in &self.racers {
+                if racer.away {
+                    lines.push(format!("player {} lost connection, waiting for them to come back", number + 1));
+                }
+            }
+            if self.opponents_left() {
+                lines.push("Everyone else left. Keep going or press Enter to return to the lobby".to_string());
+            }
+            let mut y = 580.0;
+            for line in lines {
+                canvas.draw(
+                    &graphics::Text::new(line),
+                    graphics::DrawParam::new()
+                        .dest([750.0, y])
+                        .color(Color::RED),
+                );
+                y += 30.0;
+            }
+        }
+        if !self.draw_menu.in_menu {
+            let hints = if self.solo { format!("hints {} (H)", self.hint.left) } else { "no hints in races".to_string() };
+            canvas.draw(
+                &graphics::Text::new(hints),
+                graphics::DrawParam::new()
+                    .dest([0.0, 40.0])
+                    .color(Color::WHITE),
+            );
+            self.draw_keys(&mut canvas);
+        }
         canvas.finish(ctx)?;
 
         Ok(())
     }
-    fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, _repeated: bool) -> Result<(), GameError> {
+    fn key_down_event(&mut self, _ctx: &mut Context, input: KeyInput, _repeated: bool) -> Result<(), GameError> {
+        if self.draw_menu.in_menu && self.draw_menu.typing {
+            match input.keycode {
+                Some(KeyCode::Back) => { self.draw_menu.input().and_then(|input| input.pop()); },
+                // Join 주소를 고칠 때 위아래로 LAN 에서 찾은 서버를 고른다
+                Some(key @ (KeyCode::Up | KeyCode::Down)) if self.draw_menu.list[self.draw_menu.select as usize] == "Join" => {
+                    let found = self.discovery.found();
+                    if !found.is_empty() {
+                        let last = found.len() - 1;
+                        let index = match (self.lan_select, key) {
+                            (None, _) => 0,
+                            (Some(i), KeyCode::Up) => i.saturating_sub(1),
+                            (Some(i), _) => (i + 1).min(last),
+                        }.min(last);
+                        self.draw_menu.server_input = found[index].addr.to_string();
+                        self.lan_select = Some(index);
+                    }
+                }
+                // Join 은 주소를 확인하면 바로 접속한다
+                Some(KeyCode::Return) if self.draw_menu.list[self.draw_menu.select as usize] == "Join" => {
+                    self.draw_menu.typing = false;
+                    self.draw_menu.join();
+                }
+                Some(KeyCode::Return) | Some(KeyCode::Escape) => self.draw_menu.typing = false,
+                _ => {}
+            }
+            return Ok(());
+        }
+        if !self.draw_menu.in_menu && self.browsing {
+            match input.keycode {
+                Some(KeyCode::Up) => self.room_select = self.room_select.saturating_sub(1),
+                Some(KeyCode::Down) => self.room_select = (self.room_select + 1).min(self.rooms.len().saturating_sub(1)),
+                Some(KeyCode::R) => self.send(Message::ListRooms),
+                Some(KeyCode::Return) => {
+                    if let Some(room) = self.rooms.get(self.room_select) {
+                        self.notice.clear();
+                        self.send(Message::JoinRoom { room: room.name.clone() });
+                    }
+                }
+                // 시작 전이면 미로가 올 때까지 기다리고, 이미 달리는 중이면 서버가 지금까지의 판을 보내 준다
+                Some(KeyCode::W) => {
+                    if let Some(room) = self.rooms.get(self.room_select).map(|room| room.name.clone()) {
+                        self.notice.clear();
+                        self.me = None;
+                        self.racers.clear();
+                        self.browsing = false;
+                        self.spectating = true;
+                        self.waiting = true;
+                        self.send(Message::Watch { room });
+                    }
+                }
+                Some(KeyCode::Escape) => self.leave_server(),
+                _ => {}
+            }
+            return Ok(());
+        }
+        if !self.draw_menu.in_menu && !self.solo && !self.spectating && self.results.is_some() && input.keycode == Some(KeyCode::R) {
+            self.send(Message::Rematch);
+            return Ok(());
+        }
+        // 끝난 판에서 Enter 를 누르면 접속은 그대로 두고 로비의 방 목록으로 돌아간다
+        let finished = self.results.is_some() || self.opponents_left();
+        if !self.draw_menu.in_menu && !self.solo && finished && input.keycode == Some(KeyCode::Return) && self.socket_client.is_some() {
+            self.send(Message::LeaveRoom);
+            self.back_to_lobby();
+            return Ok(());
+        }
+        let leave = input.keycode == Some(KeyCode::Escape) || (finished && input.keycode == Some(KeyCode::Return));
+        if !self.draw_menu.in_menu && !self.solo && leave {
+            self.send(Message::LeaveRoom);
+            self.leave_server();
+            return Ok(());
+        }
+        if let (true, Some(_), Some(key)) = (self.draw_menu.in_menu, self.draw_menu.board, input.keycode) {
+            self.draw_menu.go_board(key, self.leaderboard.categories().len());
+            return Ok(());
+        }
+        // 상대가 있는 판은 멈출 수 없다
+        if !self.draw_menu.in_menu && self.solo && self.generating.is_none() && input.keycode == Some(KeyCode::P) {
+            self.timer.paused = !self.timer.paused;
+            return Ok(());
+        }
+        if !self.draw_menu.in_menu && input.keycode == Some(KeyCode::H) {
+            self.use_hint();
+            return Ok(());
+        }
         if let Some(dir) = input.keycode.and_then(Direction::from_keycode) {
             if !self.draw_menu.in_menu {
-                self.player.go(dir, self.solo,self.map.clone());
+                let blocked = self.timer.paused || self.waiting || self.locked || self.spectating || self.generating.is_some();
+                if let (false, Some(dir)) = (blocked, dir.to_core()) {
+                    if !self.solo {
+                        if self.game.outcome == Outcome::Playing {
+                            self.send(Message::Move { dir });
+                        }
+                    } else if self.game.step(dir) {
+                        self.moved();
+                        if enemy::caught(&self.enemies, self.game.player) {
+                            self.game.outcome = Outcome::Bombed;
+                        }
+                    }
+                }
             }else{
                 self.draw_menu.go(dir);
             }
@@ -806,6 +1671,12 @@ impl EventHandler for MyGame {
         }
         Ok(())
     }
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) -> Result<(), GameError> {
+        if self.draw_menu.in_menu {
+            self.draw_menu.type_char(character);
+        }
+        Ok(())
+    }
 }
 
 
@@ -815,7 +1686,7 @@ fn main() {
         .window_mode(ggez::conf::WindowMode::default().dimensions(1900.0, 1200.0))
         .build()
         .expect("aieee, could not create ggez context!");
-    let my_game = unsafe { MyGame::new(&mut ctx) };
+    let my_game = MyGame::new(&mut ctx);
     // Run!
     event::run(ctx, event_loop, my_game);
 
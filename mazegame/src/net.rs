@@ -0,0 +1,88 @@
+use std::io;
+use std::net::{Shutdown, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use maze_core::protocol::{self, Message};
+
+// 1초마다 Ping 에 Pong 이 오므로 이만큼 조용하면 서버가 사라진 것으로 본다
+const SILENCE: Duration = Duration::from_secs(5);
+
+pub(crate) enum Event {
+    Message(Message),
+    Closed(String),
+}
+
+// 접속, 읽기, 쓰기는 모두 뒤쪽 스레드에서 한다. update 는 채널만 들여다보므로 멈추지 않는다
+pub(crate) struct Connection {
+    outgoing: Sender<Message>,
+    incoming: Receiver<Event>,
+}
+
+impl Connection {
+    pub fn connect(addr: &str) -> Self {
+        let (outgoing, to_send) = mpsc::channel();
+        let (received, incoming) = mpsc::channel();
+        let addr = addr.to_string();
+        thread::spawn(move || {
+            let socket = match open(&addr) {
+                Ok(socket) => socket,
+                Err(e) => {
+                    let _ = received.send(Event::Closed(format!("Failed to connect to server: {}", e)));
+                    return;
+                }
+            };
+            let mut reader = match socket.try_clone() {
+                Ok(reader) => reader,
+                Err(e) => {
+                    let _ = received.send(Event::Closed(e.to_string()));
+                    return;
+                }
+            };
+            thread::spawn(move || loop {
+                match protocol::read_message(&mut reader) {
+                    Ok(message) => {
+                        if received.send(Event::Message(message)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        let _ = received.send(Event::Closed(format!("Failed to receive data from server: {}", e)));
+                        break;
+                    }
+                }
+            });
+            write_all(socket, to_send);
+        });
+        Connection { outgoing, incoming }
+    }
+
+    // 아직 접속 중이면 쌓아 두었다가 접속되는 대로 보낸다
+    pub fn send(&self, message: Message) {
+        let _ = self.outgoing.send(message);
+    }
+
+    pub fn try_recv(&self) -> Option<Event> {
+        self.incoming.try_recv().ok()
+    }
+}
+
+fn open(addr: &str) -> io::Result<TcpStream> {
+    let mut socket = TcpStream::connect(addr)?;
+    protocol::handshake(&mut socket)?;
+    socket.set_nodelay(true)?;
+    socket.set_read_timeout(Some(SILENCE))?;
+    Ok(socket)
+}
+
+// Connection 이 버려지면 남은 메시지까지 보낸 뒤 소켓을 닫아 읽기 스레드도 끝낸다
+fn write_all(mut socket: TcpStream, to_send: Receiver<Message>) {
+    for message in to_send {
+        if let Err(e) = protocol::write_message(&mut socket, &message) {
+            println!("Failed to send to server: {}", e);
+            break;
+        }
+    }
+    let _ = socket.shutdown(Shutdown::Both);
+}
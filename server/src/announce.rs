@@ -0,0 +1,39 @@
+use std::net::UdpSocket;
+use std::time::{Duration, Instant};
+
+use maze_core::protocol::Beacon;
+
+const INTERVAL: Duration = Duration::from_secs(1);
+
+// LAN 에 Beacon 을 INTERVAL 마다 뿌린다. 같은 컴퓨터에서 시험할 때는 target 을 127.0.0.1:8089 로
+pub(crate) struct Announcer {
+    socket: UdpSocket,
+    target: String,
+    last: Option<Instant>,
+    failing: bool,
+}
+
+impl Announcer {
+    pub fn new(target: &str) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.set_broadcast(true)?;
+        Ok(Announcer { socket, target: target.to_string(), last: None, failing: false })
+    }
+
+    pub fn due(&self) -> bool {
+        self.last.is_none_or(|last| last.elapsed() >= INTERVAL)
+    }
+
+    // 실패는 처음 한 번만 알린다
+    pub fn send(&mut self, beacon: &Beacon) {
+        self.last = Some(Instant::now());
+        match self.socket.send_to(&beacon.encode(), &self.target) {
+            Ok(_) => self.failing = false,
+            Err(e) if !self.failing => {
+                println!("Failed to announce to {}: {}", self.target, e);
+                self.failing = true;
+            }
+            Err(_) => {}
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::net::IpAddr;
+use std::{env, fs};
+
+use maze_core::protocol::{DEFAULT_PORT, DISCOVERY_PORT};
+use maze_core::{BombRules, EnemyRules};
+
+const CONFIG_FILE: &str = "server.cfg";
+
+// server.cfg 예시 (명령줄 옵션이 파일보다 우선한다)
+//   # 같은 LAN 의 다른 컴퓨터에서 들어오게 하려면 0.0.0.0
+//   bind = 0.0.0.0
+//   port = 8088
+//   # Join 메뉴의 LAN 목록에 보일 이름과 Beacon 을 보낼 곳 (off 면 보내지 않는다).
+//   # 적지 않으면 LAN 전체에 뿌리고, bind 가 127.0.0.1 처럼 이 컴퓨터에서만 들어올 수 있으면 이 컴퓨터에만 알린다
+//   name = lab
+//   announce = 255.255.255.255:8089
+//   # 폭탄이 Go 뒤 몇 초에 터지는지와 폭발 범위 (칸)
+//   fuse = 30
+//   blast = 2
+//   # 어두운 미로: 플레이어가 볼 수 있는 거리 (칸). 0 이면 미로 전체가 보인다
+//   sight = 0
+//   # 적이 한 칸 움직이는 데 걸리는 시간 (밀리초) 과 플레이어를 알아보는 거리 (칸)
+//   enemy_step = 400
+//   enemy_sight = 6
+pub(crate) struct Config {
+    pub(crate) bind: String,
+    pub(crate) port: u16,
+    pub(crate) name: String,
+    pub(crate) announce: Option<String>,
+    pub(crate) bomb: BombRules,
+    pub(crate) sight: u8,
+    pub(crate) enemy: EnemyRules,
+}
+
+const USAGE: &str = "usage: server [--config FILE] [--bind ADDRESS] [--port PORT] [--name NAME] [--announce ADDRESS|off] [--fuse SECONDS] [--blast CELLS] [--sight CELLS] [--enemy-step MS] [--enemy-sight CELLS]";
+
+impl Config {
+    pub fn load() -> Result<Self, String> {
+        let args: Vec<String> = env::args().skip(1).collect();
+        let mut config = Config {
+            bind: "127.0.0.1".to_string(),
+            port: DEFAULT_PORT,
+            name: "maze server".to_string(),
+            announce: None,
+            bomb: BombRules::default(),
+            sight: 0,
+            enemy: EnemyRules::default(),
+        };
+        let file = match args.iter().position(|arg| arg == "--config") {
+            Some(i) => args.get(i + 1).ok_or(USAGE)?.clone(),
+            None => CONFIG_FILE.to_string(),
+        };
+        let mut announce_set = false;
+        if let Ok(text) = fs::read_to_string(&file) {
+            for line in text.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let (key, value) = match line.split_once('=') {
+                    Some((key, value)) => (key.trim(), value.trim()),
+                    None => continue,
+                };
+                config.set(key, value).map_err(|e| format!("{}: {}", file, e))?;
+                announce_set |= key == "announce";
+            }
+        }
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let key = match arg.as_str() {
+                "--config" => {
+                    args.next();
+                    continue;
+                }
+                "--bind" => "bind",
+                "--port" => "port",
+                "--name" => "name",
+                "--announce" => "announce",
+                "--fuse" => "fuse",
+                "--blast" => "blast",
+                "--sight" => "sight",
+                "--enemy-step" => "enemy_step",
+                "--enemy-sight" => "enemy_sight",
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ => return Err(format!("unknown option '{}'\n{}", arg, USAGE)),
+            };
+            let value = args.next().ok_or(USAGE)?;
+            config.set(key, value)?;
+            announce_set |= key == "announce";
+        }
+        // 다른 컴퓨터가 찾아도 들어올 수 없는 서버를 LAN 에 알리지 않는다
+        if !announce_set {
+            let local = config.bind == "localhost" || config.bind.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback());
+            let target = if local { "127.0.0.1" } else { "255.255.255.255" };
+            config.announce = Some(format!("{}:{}", target, DISCOVERY_PORT));
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "bind" => self.bind = value.to_string(),
+            "port" => self.port = value.parse().map_err(|_| format!("'{}' is not a port number", value))?,
+            "name" => self.name = value.to_string(),
+            "announce" if value == "off" => self.announce = None,
+            "announce" => self.announce = Some(value.to_string()),
+            "fuse" => match value.parse::<u32>() {
+                Ok(secs) if secs > 0 => self.bomb.fuse_ms = secs.saturating_mul(1000),
+                _ => return Err(format!("'{}' is not a number of seconds", value)),
+            },
+            "blast" => self.bomb.radius = value.parse().map_err(|_| format!("'{}' is not a blast radius", value))?,
+            "sight" => self.sight = value.parse().map_err(|_| format!("'{}' is not a sight radius", value))?,
+            "enemy_step" => match value.parse::<u32>() {
+                Ok(ms) if ms > 0 => self.enemy.step_ms = ms,
+                _ => return Err(format!("'{}' is not a number of milliseconds", value)),
+            },
+            "enemy_sight" => self.enemy.sight = value.parse().map_err(|_| format!("'{}' is not a sight radius", value))?,
+            _ => return Err(format!("unknown key '{}'", key)),
+        }
+        Ok(())
+    }
+
+    pub fn addr(&self) -> String {
+        format!("{}:{}", self.bind, self.port)
+    }
+}
@@ -1,59 +1,303 @@
-use std::net::TcpListener;
+mod announce;
+mod config;
+mod room;
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
 use std::{io, thread};
-use std::io::{Read, Write};
 
-fn main() -> io::Result<()> {
-    let listener = TcpListener::bind("127.0.0.1:8088")?;
-    println!("Server listening on port 8080");
-    let (mut client_1_socket, client_1_addr) = listener.accept()?;
-    println!("Client 1 connected from {}", client_1_addr);
-
-    let (mut client_2_socket, client_2_addr) = listener.accept()?;
-    println!("Client 2 connected from {}", client_2_addr);
-
-    let mut client_1_clone = client_1_socket.try_clone()?;
-    let mut client_2_clone = client_2_socket.try_clone()?;
-    let mut buffer = [0u8; 900];
-    match client_1_clone.read(&mut buffer) {
-        Ok(_) => {
-            // println!("{:?}", buffer);
-            client_2_clone.write_all(&buffer).unwrap();
-        },
+use maze_core::protocol::{self, Beacon, Message, VERSION};
+
+use announce::Announcer;
+use room::Room;
+
+// 클라이언트는 1초마다 Ping 을 보내므로 이만큼 조용하면 끊긴 것으로 본다.
+// 보내는 쪽도 마찬가지로, 읽지 않는 클라이언트에게 이만큼 보내지 못하면 끊는다
+const SILENCE: Duration = Duration::from_secs(5);
+// 초읽기와 Beacon 을 놓치지 않도록 메시지가 없어도 이만큼마다 한 바퀴 돈다
+const TICK: Duration = Duration::from_millis(100);
+
+enum Event {
+    Connected(usize, Sender<Message>),
+    Message(usize, Message),
+    Closed(usize),
+}
+
+// 보낼 메시지는 접속마다 있는 쓰기 스레드로 넘긴다. 읽지 않는 클라이언트가 있어도 메인 루프는 멈추지 않는다
+pub(crate) struct Client {
+    outgoing: Sender<Message>,
+    room: Option<String>,
+    connected: bool,
+}
+
+pub(crate) type Clients = HashMap<usize, Client>;
+
+pub(crate) fn send(client: &mut Client, message: &Message) {
+    if client.connected && client.outgoing.send(message.clone()).is_err() {
+        client.connected = false;
+    }
+}
+
+// Client 가 버려지면 남은 메시지까지 보낸 뒤 소켓을 닫는다. 보내다 막히면 (SILENCE) 바로 닫아서
+// 읽기 스레드가 Closed 를 보내게 하고, 판 도중이면 방이 자리를 맡아 둔다
+fn write_all(id: usize, mut socket: TcpStream, to_send: Receiver<Message>) {
+    for message in to_send {
+        if let Err(e) = protocol::write_message(&mut socket, &message) {
+            println!("Failed to send data to client {}: {}", id, e);
+            break;
+        }
+    }
+    let _ = socket.shutdown(Shutdown::Both);
+}
+
+// 접속마다 스레드 하나가 Hello 를 주고받은 뒤 메시지를 읽어 events 로 넘기고, 쓰기 스레드를 하나 더 띄운다
+fn serve(id: usize, mut socket: TcpStream, events: Sender<Event>) {
+    let addr = socket.peer_addr().map_or("?".to_string(), |addr| addr.to_string());
+    if let Err(e) = protocol::handshake(&mut socket) {
+        println!("Rejected {}: {}", addr, e);
+        return;
+    }
+    println!("Client {} connected from {}", id, addr);
+    if let Err(e) = socket.set_read_timeout(Some(SILENCE)).and_then(|_| socket.set_write_timeout(Some(SILENCE))) {
+        println!("Failed to set up client {}: {}", id, e);
+        return;
+    }
+    match socket.try_clone() {
+        Ok(writer) => {
+            let (outgoing, to_send) = mpsc::channel();
+            thread::spawn(move || write_all(id, writer, to_send));
+            if events.send(Event::Connected(id, outgoing)).is_err() {
+                return;
+            }
+        }
         Err(e) => {
-            println!("Failed to receive data from server: {}", e);
+            println!("Failed to set up client {}: {}", id, e);
+            return;
         }
     }
-    let client_1 = thread::spawn(move || {
-        let mut buffer = [0u8; 4];
-        loop {
-            match client_1_clone.read(&mut buffer) {
-                Ok(_) => {
-                    // println!("{:?}", buffer);
-                    client_2_clone.write_all(&buffer).unwrap();
-                },
-                Err(e) => {
-                    println!("Failed to receive data from server: {}", e);
+    loop {
+        match protocol::read_message(&mut socket) {
+            Ok(message) => {
+                if events.send(Event::Message(id, message)).is_err() {
+                    break;
                 }
+            },
+            Err(e) => {
+                println!("Failed to receive data from client {}: {}", id, e);
+                let _ = events.send(Event::Closed(id));
+                break;
             }
         }
-    });
-    let client_2 = thread::spawn(move || {
-        let mut buffer = [0u8; 4];
-        loop {
-            match client_2_socket.read(&mut buffer) {
-                Ok(_) => {
-                    // println!("{:?}", buffer);
-                    client_1_socket.write_all(&buffer).unwrap();
-                },
-                Err(e) => {
-                    println!("Failed to receive data from server: {}", e);
-                    break;
+    }
+}
+
+// 방을 만들 때와 들어가거나 구경할 때 같은 이름이 되도록 앞뒤 공백을 떼고 MAX_NAME 글자로 자른다
+fn room_name(name: &str) -> String {
+    protocol::short_name(name.trim())
+}
+
+fn refuse(clients: &mut Clients, id: usize, reason: &str) {
+    if let Some(client) = clients.get_mut(&id) {
+        send(client, &Message::Refused { reason: reason.to_string() });
+    }
+}
+
+fn leave_room(clients: &mut Clients, rooms: &mut BTreeMap<String, Room>, id: usize) {
+    let name = match clients.get_mut(&id).and_then(|client| client.room.take()) {
+        Some(name) => name,
+        None => return,
+    };
+    if let Some(room) = rooms.get_mut(&name) {
+        room.leave(id, clients);
+    }
+}
+
+// 판 도중 연결이 끊기면 방이 자리를 잠시 맡아 둔다
+fn drop_client(clients: &mut Clients, rooms: &mut BTreeMap<String, Room>, id: usize) {
+    let name = match clients.get_mut(&id).and_then(|client| client.room.take()) {
+        Some(name) => name,
+        None => return,
+    };
+    if let Some(room) = rooms.get_mut(&name) {
+        room.disconnect(id, clients);
+    }
+}
+
+fn main() -> io::Result<()> {
+    let config = match config::Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let listener = TcpListener::bind(config.addr())?;
+    let port = listener.local_addr()?.port();
+    println!("Server listening on {}", listener.local_addr()?);
+    let mut announcer = match &config.announce {
+        Some(target) => match Announcer::new(target) {
+            Ok(announcer) => Some(announcer),
+            Err(e) => {
+                println!("LAN announcements disabled: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let (events, incoming) = mpsc::channel();
+    thread::spawn(move || {
+        for (id, socket) in listener.incoming().enumerate() {
+            match socket {
+                Ok(socket) => {
+                    let events = events.clone();
+                    thread::spawn(move || serve(id, socket, events));
                 }
+                Err(e) => println!("Failed to accept: {}", e),
             }
         }
     });
 
-    client_1.join().unwrap();
-    client_2.join().unwrap();
+    let mut clients: Clients = HashMap::new();
+    let mut rooms: BTreeMap<String, Room> = BTreeMap::new();
+    loop {
+        for room in rooms.values_mut() {
+            room.tick(&mut clients);
+        }
+        // 빈 방을 정리하고, 남은 구경꾼은 로비로 돌려보낸다. 끝난 방은 다시 할 수 있도록 모두 나갈 때까지 둔다
+        rooms.retain(|name, room| {
+            if !room.is_empty() {
+                return true;
+            }
+            // 판이 끝나기 전에 모두 나갔으면 구경꾼에게 알린다
+            let closed = Message::Refused { reason: format!("room '{}' closed", name) };
+            for id in room.members() {
+                if let Some(client) = clients.get_mut(&id) {
+                    client.room = None;
+                    if !room.is_over() {
+                        send(client, &closed);
+                    }
+                }
+            }
+            println!("Room '{}' closed", name);
+            false
+        });
+        if let Some(announcer) = announcer.as_mut().filter(|announcer| announcer.due()) {
+            announcer.send(&Beacon {
+                version: VERSION,
+                name: config.name.clone(),
+                port,
+                players: clients.len() as u16,
+                rooms: rooms.values().map(Room::info).collect(),
+            });
+        }
+        let event = match incoming.recv_timeout(TICK) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        match event {
+            Event::Connected(id, outgoing) => {
+                clients.insert(id, Client { outgoing, room: None, connected: true });
+            }
+            Event::Message(id, Message::ListRooms) => {
+                let list = Message::RoomList { rooms: rooms.values().map(Room::info).collect() };
+                if let Some(client) = clients.get_mut(&id) {
+                    send(client, &list);
+                }
+            }
+            Event::Message(id, Message::CreateRoom { room, players, seed, algorithm, width, height, bombs, doors, enemies }) => {
+                let room = room_name(&room);
+                if room.is_empty() {
+                    refuse(&mut clients, id, "room name must not be empty");
+                    continue;
+                }
+                if rooms.contains_key(&room) {
+                    refuse(&mut clients, id, &format!("room '{}' already exists", room));
+                    continue;
+                }
+                leave_room(&mut clients, &mut rooms, id);
+                println!("Client {} created room '{}'", id, room);
+                let mut new_room = Room::new(room.clone(), players, seed, algorithm, (width, height), (bombs, doors, enemies), &config);
+                new_room.join(id, &mut clients);
+                rooms.insert(room.clone(), new_room);
+                if let Some(client) = clients.get_mut(&id) {
+                    client.room = Some(room);
+                }
+            }
+            Event::Message(id, Message::JoinRoom { room }) => {
+                let room = room_name(&room);
+                match rooms.get(&room) {
+                    Some(r) if r.is_open() => {}
+                    Some(_) => {
+                        refuse(&mut clients, id, &format!("room '{}' is full or already playing", room));
+                        continue;
+                    }
+                    None => {
+                        refuse(&mut clients, id, &format!("no room named '{}'", room));
+                        continue;
+                    }
+                }
+                leave_room(&mut clients, &mut rooms, id);
+                if let Some(r) = rooms.get_mut(&room) {
+                    r.join(id, &mut clients);
+                }
+                if let Some(client) = clients.get_mut(&id) {
+                    client.room = Some(room);
+                }
+            }
+            Event::Message(id, Message::Watch { room }) => {
+                let room = room_name(&room);
+                if !rooms.contains_key(&room) {
+                    refuse(&mut clients, id, &format!("no room named '{}'", room));
+                    continue;
+                }
+                leave_room(&mut clients, &mut rooms, id);
+                if let Some(r) = rooms.get_mut(&room) {
+                    r.watch(id, &mut clients);
+                }
+                println!("Client {} is watching room '{}'", id, room);
+                if let Some(client) = clients.get_mut(&id) {
+                    client.room = Some(room);
+                }
+            }
+            Event::Message(id, Message::Rejoin { token }) => {
+                let name = rooms.iter().find(|(_, room)| room.has_token(token)).map(|(name, _)| name.clone());
+                let name = match name {
+                    Some(name) => name,
+                    None => {
+                        refuse(&mut clients, id, "that game is over or your seat was given up");
+                        continue;
+                    }
+                };
+                leave_room(&mut clients, &mut rooms, id);
+                if let Some(room) = rooms.get_mut(&name) {
+                    room.rejoin(token, id, &mut clients);
+                }
+                println!("Client {} rejoined room '{}'", id, name);
+                if let Some(client) = clients.get_mut(&id) {
+                    client.room = Some(name);
+                }
+            }
+            Event::Message(id, Message::LeaveRoom) => leave_room(&mut clients, &mut rooms, id),
+            Event::Message(id, Message::Ping { nonce }) => {
+                if let Some(client) = clients.get_mut(&id) {
+                    send(client, &Message::Pong { nonce });
+                }
+            }
+            Event::Message(id, message) => {
+                let name = clients.get(&id).and_then(|client| client.room.clone());
+                if let Some(room) = name.and_then(|name| rooms.get_mut(&name)) {
+                    room.handle(id, message, &mut clients);
+                }
+            }
+            Event::Closed(id) => {
+                drop_client(&mut clients, &mut rooms, id);
+                clients.remove(&id);
+            }
+        }
+
+    }
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,605 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use maze_core::protocol::{self, Finish, Message, Placing, RoomInfo};
+use maze_core::enemy::{self, Enemy};
+use maze_core::{generator, Behavior, BombRules, EnemyRules, GameState, Outcome, DOORS, KEYS};
+
+use crate::config::Config;
+use crate::{send, Clients};
+
+const MIN_PLAYERS: u8 = 2;
+const MAX_PLAYERS: u8 = 8;
+const MIN_SIZE: u16 = 5;
+const MAX_SIZE: u16 = 2000;
+const BOMB_SHARE: u32 = 50;
+const MAX_BOMBS: u32 = 2000;
+const MAX_ENEMIES: usize = 8;
+// 판 도중 끊긴 사람이 같은 token 으로 돌아올 수 있는 시간
+const RECONNECT_WINDOW: Duration = Duration::from_secs(30);
+// 미로를 받고 모두 Ready 하면 (아무리 늦어도 READY_WAIT 뒤에는) COUNTDOWN 초를 세고 출발한다
+const READY_WAIT: Duration = Duration::from_secs(10);
+const COUNTDOWN: u8 = 3;
+
+// 한 자리. 연결이 끊겨도 RECONNECT_WINDOW 동안은 away 로 남아 자리와 GameState 를 지킨다
+struct Seat {
+    client: usize,
+    token: u64,
+    away: Option<Instant>,
+    ready: bool,
+    // 판이 끝난 뒤 한 판 더 하자고 했다
+    rematch: bool,
+    game: Option<GameState>,
+}
+
+// 한 판. 자리 번호가 곧 플레이어 번호이고, 판이 시작되면 자리마다 자기 GameState 로 움직인다.
+// 모두 탈출하거나 폭탄에 맞거나 나갈 때까지 달리고, 끝나면 서버가 순위를 정한다.
+// 끝난 뒤 남은 사람이 모두 Rematch 하면 새 seed 로 같은 방을 처음부터 다시 연다
+pub(crate) struct Room {
+    pub(crate) name: String,
+    seed: u64,
+    algorithm: String,
+    width: u16,
+    height: u16,
+    bombs: u16,
+    doors: u8,
+    seats: Vec<Option<Seat>>,
+    // 구경만 하는 사람. 방의 메시지를 모두 받지만 움직일 수 없다
+    spectators: Vec<usize>,
+    map: Vec<Vec<char>>,
+    // 다른 방을 막지 않도록 미로는 따로 스레드에서 만든다. 다 만들어지면 tick 이 받아 판을 연다
+    generating: Option<Receiver<GameState>>,
+    // 미로를 나눠 준 때와 출발하는 때. count 는 마지막으로 보낸 Countdown 이고 Go 를 보냈으면 0
+    started: Option<Instant>,
+    go: Option<Instant>,
+    count: u8,
+    bomb: BombRules,
+    // 어두운 미로에서 보이는 거리. 0 이면 모두 보인다
+    sight: u8,
+    // 판마다 seed 로 놓는 적의 종류와 이번 판의 적. enemy_steps 는 Go 뒤 지금까지 움직인 횟수
+    enemy_kinds: Vec<Behavior>,
+    enemy_rules: EnemyRules,
+    enemies: Vec<Enemy>,
+    enemy_steps: u32,
+    // 이번 판 폭탄 자리. 모두 같은 fuse 로 한꺼번에 터진다
+    bomb_cells: Vec<(usize, usize)>,
+    exploded: bool,
+    // 자리마다 어떻게 끝냈는지. 끝나기 전에 나가도 이미 낸 기록은 남는다
+    finishes: Vec<Option<Finish>>,
+    over: bool,
+}
+
+// 알 수 없는 알고리즘은 dfs 로 만든다
+fn create_game(seed: u64, algorithm: &str, width: u16, height: u16, bombs: u16, doors: u8) -> GameState {
+    let generator = generator::by_name(algorithm).unwrap_or_else(|| {
+        println!("unknown maze algorithm '{}', using dfs", algorithm);
+        generator::by_name("dfs").unwrap()
+    });
+    GameState::generate(seed, generator.as_ref(), width as usize, height as usize, bombs as usize, doors as usize)
+}
+
+// 다른 사람이 짐작할 수 없으면 충분하다 (token 과 다시 하는 판의 seed)
+fn random() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_nanos());
+    hasher.finish()
+}
+
+impl Room {
+    // 클라이언트가 보낸 설정은 믿지 않고 사람 수와 크기, 폭탄 수 (칸 BOMB_SHARE 개마다 하나, 많아도 MAX_BOMBS 개까지), 문과 적의 수를 범위 안으로 고친다
+    pub fn new(name: String, players: u8, seed: u64, algorithm: String, size: (u16, u16), (bombs, doors, mut enemies): (u16, u8, Vec<Behavior>), config: &Config) -> Self {
+        let players = players.clamp(MIN_PLAYERS, MAX_PLAYERS) as usize;
+        let (width, height) = (size.0.clamp(MIN_SIZE, MAX_SIZE), size.1.clamp(MIN_SIZE, MAX_SIZE));
+        let bombs = bombs.clamp(1, (width as u32 * height as u32 / BOMB_SHARE).clamp(1, MAX_BOMBS) as u16);
+        let doors = doors.min(KEYS.len() as u8);
+        enemies.truncate(MAX_ENEMIES);
+        Room {
+            name,
+            seed,
+            algorithm,
+            width,
+            height,
+            bombs,
+            doors,
+            seats: (0..players).map(|_| None).collect(),
+            spectators: Vec::new(),
+            map: Vec::new(),
+            generating: None,
+            started: None,
+            go: None,
+            count: COUNTDOWN,
+            bomb: config.bomb,
+            sight: config.sight,
+            enemy_kinds: enemies,
+            enemy_rules: config.enemy,
+            enemies: Vec::new(),
+            enemy_steps: 0,
+            bomb_cells: Vec::new(),
+            exploded: false,
+            finishes: vec![None; players],
+            over: false,
+        }
+    }
+
+    pub fn info(&self) -> RoomInfo {
+        RoomInfo {
+            name: self.name.clone(),
+            players: self.seats.iter().flatten().count() as u8,
+            capacity: self.seats.len() as u8,
+            playing: self.started.is_some(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.started.is_none() && self.seats.iter().any(Option::is_none)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seats.iter().all(Option::is_none)
+    }
+
+    // 판이 끝났다. 한 판 더 할 수 있도록 방은 모두 나갈 때까지 남고, 나간 사람은 로비로 돌아간다
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+
+    // 지금 연결되어 있는 사람 (구경꾼 포함)
+    pub fn members(&self) -> impl Iterator<Item = usize> + '_ {
+        let players = self.seats.iter().flatten().filter(|seat| seat.away.is_none()).map(|seat| seat.client);
+        players.chain(self.spectators.iter().copied())
+    }
+
+    pub fn has_token(&self, token: u64) -> bool {
+        self.seats.iter().flatten().any(|seat| seat.token == token)
+    }
+
+    fn seat_of(&self, client: usize) -> Option<usize> {
+        self.seats.iter().position(|seat| seat.as_ref().is_some_and(|seat| seat.client == client && seat.away.is_none()))
+    }
+
+    fn broadcast(&self, clients: &mut Clients, message: &Message) {
+        for client in self.members() {
+            if let Some(client) = clients.get_mut(&client) {
+                send(client, message);
+            }
+        }
+    }
+
+    // 빈 자리에 앉히고, 자리가 다 차면 시작한다
+    pub fn join(&mut self, client: usize, clients: &mut Clients) {
+        let seat = match self.seats.iter().position(Option::is_none) {
+            Some(seat) => seat,
+            None => return,
+        };
+        let token = random();
+        self.seats[seat] = Some(Seat { client, token, away: None, ready: false, rematch: false, game: None });
+        if let Some(c) = clients.get_mut(&client) {
+            send(c, &Message::Welcome { player: seat as u8, token });
+        }
+        if self.seats.iter().all(Option::is_some) {
+            self.start();
+        }
+    }
+
+    // 미로를 만들기 시작한다. 이미 만드는 중이면 그것을 기다린다
+    fn start(&mut self) {
+        if self.generating.is_some() {
+            return;
+        }
+        let (done, generating) = mpsc::channel();
+        let (seed, algorithm, width, height, bombs, doors) = (self.seed, self.algorithm.clone(), self.width, self.height, self.bombs, self.doors);
+        thread::spawn(move || {
+            let _ = done.send(create_game(seed, &algorithm, width, height, bombs, doors));
+        });
+        self.generating = Some(generating);
+    }
+
+    // 미로가 다 만들어졌고 그 사이 자리가 비지 않았으면 나눠 주고 판을 연다. 자리가 비었으면 다시 찰 때까지 미로를 둔다
+    fn begin(&mut self, clients: &mut Clients) {
+        if !self.seats.iter().all(Option::is_some) {
+            return;
+        }
+        let game = match self.generating.as_ref().map(Receiver::try_recv) {
+            Some(Ok(game)) => game,
+            Some(Err(TryRecvError::Empty)) | None => return,
+            Some(Err(TryRecvError::Disconnected)) => {
+                println!("room '{}': maze generation failed", self.name);
+                self.generating = None;
+                return;
+            }
+        };
+        self.generating = None;
+        let doors = game.map.iter().flatten().filter(|&&cell| DOORS.contains(&cell)).count();
+        self.enemies = enemy::spawn(&game, &self.enemy_kinds, self.seed);
+        self.enemy_steps = 0;
+        println!("room '{}': seed {} ({}, {}x{}, {} bombs, {} doors, {} enemies)", self.name, self.seed, self.algorithm, game.width(), game.height(), game.bombs.len(), doors, self.enemies.len());
+        self.map = game.map.clone();
+        self.bomb_cells = game.bombs.clone();
+        self.broadcast(clients, &Message::MazeData { map: game.map.clone() });
+        self.broadcast(clients, &Message::Fuse { ms: self.bomb.fuse_ms, radius: self.bomb.radius });
+        self.broadcast(clients, &Message::Sight { radius: self.sight });
+        self.broadcast(clients, &self.enemy_positions());
+        let (x, y) = game.player;
+        for number in 0..self.seats.len() {
+            if let Some(seat) = self.seats[number].as_mut() {
+                seat.game = Some(game.clone());
+            }
+            self.broadcast(clients, &Message::PlayerMove { player: number as u8, x: x as u16, y: y as u16 });
+        }
+        self.started = Some(Instant::now());
+    }
+
+    // 판이 이미 시작했으면 지금까지의 판을 보내 준다
+    pub fn watch(&mut self, client: usize, clients: &mut Clients) {
+        self.spectators.push(client);
+        if self.started.is_none() {
+            return;
+        }
+        if let Some(c) = clients.get_mut(&client) {
+            for message in &self.state() {
+                send(c, message);
+            }
+        }
+    }
+
+    // 스스로 나가면 자리를 바로 비운다
+    pub fn leave(&mut self, client: usize, clients: &mut Clients) {
+        self.spectators.retain(|&spectator| spectator != client);
+        if let Some(number) = self.seat_of(client) {
+            self.seats[number] = None;
+            if self.started.is_some() {
+                self.broadcast(clients, &Message::PlayerLeft { player: number as u8 });
+                self.check_over(clients);
+                self.check_rematch(clients);
+            }
+        }
+    }
+
+    // 연결이 끊기면 판 도중에는 자리를 맡아 두고, 시작 전이면 그냥 나간 것으로 한다
+    pub fn disconnect(&mut self, client: usize, clients: &mut Clients) {
+        let number = match self.seat_of(client) {
+            Some(number) => number,
+            // 구경꾼은 그냥 나간다
+            None => {
+                self.leave(client, clients);
+                return;
+            }
+        };
+        if self.started.is_none() || self.over {
+            self.leave(client, clients);
+            return;
+        }
+        if let Some(seat) = self.seats[number].as_mut() {
+            seat.away = Some(Instant::now());
+        }
+        println!("room '{}': player {} disconnected", self.name, number + 1);
+        self.broadcast(clients, &Message::PlayerAway { player: number as u8 });
+    }
+
+    // 같은 token 이면 새 연결을 그 자리에 앉히고 지금까지의 판을 다시 보내 준다
+    pub fn rejoin(&mut self, token: u64, client: usize, clients: &mut Clients) {
+        let number = match self.seats.iter().position(|seat| seat.as_ref().is_some_and(|seat| seat.token == token)) {
+            Some(number) => number,
+            None => return,
+        };
+        self.broadcast(clients, &Message::PlayerBack { player: number as u8 });
+        if let Some(seat) = self.seats[number].as_mut() {
+            seat.client = client;
+            seat.away = None;
+        }
+        let mut state = vec![Message::Welcome { player: number as u8, token }];
+        state.extend(self.state());
+        // 주운 열쇠와 연 문이 그대로 보이도록 미로는 자기 것으로 보낸다
+        if let Some(game) = self.seats[number].as_ref().and_then(|seat| seat.game.as_ref()) {
+            state[1] = Message::MazeData { map: game.map.clone() };
+        }
+        if let Some(c) = clients.get_mut(&client) {
+            for message in &state {
+                send(c, message);
+            }
+        }
+    }
+
+    // 미로, 모두의 위치와 승패, 끊기거나 나간 사람
+    fn state(&self) -> Vec<Message> {
+        let mut state = vec![Message::MazeData { map: self.map.clone() }];
+        match self.go {
+            Some(_) if self.count == 0 => state.push(Message::Go),
+            Some(_) => state.push(Message::Countdown { seconds: self.count }),
+            None => {}
+        }
+        state.extend(self.fuse());
+        state.push(Message::Sight { radius: self.sight });
+        state.push(self.enemy_positions());
+        for (other, seat) in self.seats.iter().enumerate() {
+            let game = match seat.as_ref().and_then(|seat| seat.game.as_ref()) {
+                Some(game) => game,
+                None => continue,
+            };
+            let player = other as u8;
+            let (x, y) = (game.player.0 as u16, game.player.1 as u16);
+            state.push(Message::PlayerMove { player, x, y });
+            match self.finishes[other] {
+                Some(Finish::Escaped { time_ms }) => state.push(Message::PlayerFinished { player, time_ms }),
+                Some(Finish::Bombed) => state.push(Message::Bombed { player, x, y }),
+                _ => {}
+            }
+        }
+        for (other, seat) in self.seats.iter().enumerate() {
+            match seat {
+                Some(seat) if seat.away.is_some() => state.push(Message::PlayerAway { player: other as u8 }),
+                None => state.push(Message::PlayerLeft { player: other as u8 }),
+                _ => {}
+            }
+        }
+        if self.over {
+            state.push(Message::GameOver { results: self.results() });
+        }
+        state
+    }
+
+    // 메인 루프가 자주 부른다. 만들어진 미로를 나눠 주고, 출발 신호를 보내고, 폭탄을 터뜨리고, 돌아오지 않는 사람을 정리한다
+    pub fn tick(&mut self, clients: &mut Clients) {
+        self.begin(clients);
+        self.check_ready(clients);
+        self.count_down(clients);
+        self.explode(clients);
+        self.hunt(clients);
+        self.expire(clients);
+    }
+
+    fn enemy_positions(&self) -> Message {
+        Message::Enemies { enemies: self.enemies.iter().map(|enemy| (enemy.behavior, enemy.pos.0 as u16, enemy.pos.1 as u16)).collect() }
+    }
+
+    // 적은 Go 부터 step_ms 마다 한 칸씩, 처음 나눠 준 미로 위를 다닌다 (터진 벽과 연 문은 모른다).
+    // 적과 만난 사람은 폭탄에 맞은 것으로 끝난다
+    fn hunt(&mut self, clients: &mut Clients) {
+        let go = match self.go {
+            Some(go) if self.count == 0 && !self.over && !self.enemies.is_empty() => go,
+            _ => return,
+        };
+        let due = (go.elapsed().as_millis() / self.enemy_rules.step_ms.max(1) as u128) as u32;
+        if self.enemy_steps >= due {
+            return;
+        }
+        let mut caught = Vec::new();
+        while self.enemy_steps < due {
+            self.enemy_steps += 1;
+            let mut players = Vec::new();
+            let mut exit = (0, 0);
+            for game in self.seats.iter().flatten().filter_map(|seat| seat.game.as_ref()) {
+                exit = game.exit;
+                if game.outcome == Outcome::Playing {
+                    players.push(game.player);
+                }
+            }
+            for enemy in &mut self.enemies {
+                enemy.step(&self.map, &players, exit, &self.enemy_rules);
+            }
+            for (number, seat) in self.seats.iter_mut().enumerate() {
+                let game = match seat.as_mut().and_then(|seat| seat.game.as_mut()) {
+                    Some(game) => game,
+                    None => continue,
+                };
+                if game.outcome == Outcome::Playing && enemy::caught(&self.enemies, game.player) {
+                    game.outcome = Outcome::Bombed;
+                    caught.push((number, game.player));
+                }
+            }
+        }
+        self.broadcast(clients, &self.enemy_positions());
+        for (number, (x, y)) in caught {
+            println!("room '{}': player {} was caught", self.name, number + 1);
+            self.finishes[number] = Some(Finish::Bombed);
+            self.broadcast(clients, &Message::Bombed { player: number as u8, x: x as u16, y: y as u16 });
+        }
+        self.check_over(clients);
+    }
+
+    // 지금 들어온 사람에게 보낼 폭탄 상태
+    fn fuse(&self) -> Vec<Message> {
+        let radius = self.bomb.radius;
+        if self.exploded {
+            return self.bomb_cells.iter().map(|&(x, y)| Message::Exploded { x: x as u16, y: y as u16, radius }).collect();
+        }
+        let fuse = Duration::from_millis(self.bomb.fuse_ms as u64);
+        let ms = match self.go {
+            Some(go) if self.count == 0 => fuse.saturating_sub(go.elapsed()).as_millis() as u32,
+            _ => self.bomb.fuse_ms,
+        };
+        vec![Message::Fuse { ms, radius }]
+    }
+
+    // 모두의 미로에서 같은 폭탄들이 터진다. 범위 안에 있던 사람은 폭탄에 맞은 것으로 끝난다
+    fn explode(&mut self, clients: &mut Clients) {
+        let go = match self.go {
+            Some(go) if self.count == 0 && !self.exploded && !self.over => go,
+            _ => return,
+        };
+        if go.elapsed() < Duration::from_millis(self.bomb.fuse_ms as u64) {
+            return;
+        }
+        self.exploded = true;
+        let radius = self.bomb.radius;
+        let mut caught = Vec::new();
+        for (number, seat) in self.seats.iter_mut().enumerate() {
+            let game = match seat.as_mut().and_then(|seat| seat.game.as_mut()) {
+                Some(game) => game,
+                None => continue,
+            };
+            let playing = game.outcome == Outcome::Playing;
+            for &cell in &self.bomb_cells {
+                game.explode(cell, radius);
+            }
+            if playing && game.outcome == Outcome::Bombed {
+                caught.push((number, game.player));
+            }
+        }
+        println!("room '{}': {} bombs went off, {} caught", self.name, self.bomb_cells.len(), caught.len());
+        for message in self.fuse() {
+            self.broadcast(clients, &message);
+        }
+        for (number, (x, y)) in caught {
+            self.finishes[number] = Some(Finish::Bombed);
+            self.broadcast(clients, &Message::Bombed { player: number as u8, x: x as u16, y: y as u16 });
+        }
+        self.check_over(clients);
+    }
+
+    // 연결되어 있는 사람이 모두 Ready 했거나 READY_WAIT 가 지나면 초읽기를 시작한다
+    fn check_ready(&mut self, clients: &mut Clients) {
+        let started = match self.started {
+            Some(started) if self.go.is_none() => started,
+            _ => return,
+        };
+        let ready = self.seats.iter().flatten().filter(|seat| seat.away.is_none()).all(|seat| seat.ready);
+        if ready || started.elapsed() >= READY_WAIT {
+            self.go = Some(Instant::now() + Duration::from_secs(COUNTDOWN as u64));
+            self.broadcast(clients, &Message::Countdown { seconds: COUNTDOWN });
+        }
+    }
+
+    fn count_down(&mut self, clients: &mut Clients) {
+        let go = match self.go {
+            Some(go) if self.count > 0 => go,
+            _ => return,
+        };
+        let left = go.saturating_duration_since(Instant::now()).as_millis().div_ceil(1000) as u8;
+        if left == 0 {
+            self.count = 0;
+            self.go = Some(Instant::now());
+            self.broadcast(clients, &Message::Go);
+        } else if left < self.count {
+            self.count = left;
+            self.broadcast(clients, &Message::Countdown { seconds: left });
+        }
+    }
+
+    // RECONNECT_WINDOW 안에 돌아오지 않은 자리를 비운다
+    fn expire(&mut self, clients: &mut Clients) {
+        for number in 0..self.seats.len() {
+            let expired = self.seats[number].as_ref().and_then(|seat| seat.away).is_some_and(|away| away.elapsed() >= RECONNECT_WINDOW);
+            if expired {
+                println!("room '{}': player {} did not come back", self.name, number + 1);
+                self.seats[number] = None;
+                self.broadcast(clients, &Message::PlayerLeft { player: number as u8 });
+                self.check_over(clients);
+            }
+        }
+    }
+
+    pub fn handle(&mut self, client: usize, message: Message, clients: &mut Clients) {
+        let number = match self.seat_of(client) {
+            Some(number) => number,
+            None => return,
+        };
+        match message {
+            Message::Ready => {
+                if let Some(seat) = self.seats[number].as_mut() {
+                    seat.ready = true;
+                }
+                self.check_ready(clients);
+            }
+            Message::Move { dir } => self.step(number, dir, clients),
+            Message::Rematch if self.over => {
+                if let Some(seat) = self.seats[number].as_mut() {
+                    seat.rematch = true;
+                }
+                self.broadcast(clients, &Message::RematchVote { player: number as u8 });
+                self.check_rematch(clients);
+            }
+            Message::Chat { .. } => self.broadcast(clients, &message),
+            other => println!("room '{}': ignoring {:?} from player {}", self.name, other, number + 1),
+        }
+    }
+
+    fn step(&mut self, number: usize, dir: maze_core::Direction, clients: &mut Clients) {
+        // Go 전에는 움직이지 않고, 기록은 Go 부터 잰다
+        let go = match self.go {
+            Some(go) if self.count == 0 => go,
+            _ => return,
+        };
+        let seat = match self.seats[number].as_mut() {
+            Some(seat) => seat,
+            None => return,
+        };
+        let game = match seat.game.as_mut() {
+            Some(game) => game,
+            None => return,
+        };
+        // 벽이나 미로 밖, 이미 끝난 판이면 움직이지 않는다
+        if self.over || !game.step(dir) {
+            return;
+        }
+        // 적이 있는 칸으로 걸어 들어가도 잡힌다
+        if game.outcome == Outcome::Playing && enemy::caught(&self.enemies, game.player) {
+            game.outcome = Outcome::Bombed;
+        }
+        let (x, y) = (game.player.0 as u16, game.player.1 as u16);
+        let outcome = game.outcome;
+        let player = number as u8;
+        let time_ms = go.elapsed().as_millis() as u32;
+        self.broadcast(clients, &Message::PlayerMove { player, x, y });
+        match outcome {
+            Outcome::Escaped => {
+                self.finishes[number] = Some(Finish::Escaped { time_ms });
+                self.broadcast(clients, &Message::PlayerFinished { player, time_ms });
+                self.check_over(clients);
+            }
+            Outcome::Bombed => {
+                self.finishes[number] = Some(Finish::Bombed);
+                self.broadcast(clients, &Message::Bombed { player, x, y });
+                self.check_over(clients);
+            }
+            Outcome::Playing => {}
+        }
+    }
+
+    // 기록 없이 자리가 빈 사람은 나간 것으로 친다
+    fn results(&self) -> Vec<Placing> {
+        let finishes: Vec<Finish> = self.finishes.iter().map(|finish| finish.unwrap_or(Finish::Left)).collect();
+        protocol::placings(&finishes)
+    }
+
+    // 남은 사람이 모두 원하면 새 seed 로 방을 처음 상태로 되돌린다. 자리가 다 차 있으면 바로 시작한다
+    fn check_rematch(&mut self, clients: &mut Clients) {
+        let mut seated = self.seats.iter().flatten().peekable();
+        if !self.over || seated.peek().is_none() || !seated.all(|seat| seat.rematch) {
+            return;
+        }
+        for seat in self.seats.iter_mut().flatten() {
+            seat.ready = false;
+            seat.rematch = false;
+            seat.game = None;
+        }
+        self.seed = random();
+        self.map = Vec::new();
+        self.started = None;
+        self.go = None;
+        self.count = COUNTDOWN;
+        self.exploded = false;
+        self.enemies = Vec::new();
+        self.finishes = vec![None; self.seats.len()];
+        self.over = false;
+        println!("room '{}': rematch", self.name);
+        self.broadcast(clients, &Message::Rematch);
+        if self.seats.iter().all(Option::is_some) {
+            self.start();
+        }
+    }
+
+    // 아직 뛰고 있는 사람이 없으면 끝 (끊겨서 돌아오길 기다리는 사람은 아직 뛰는 중)
+    fn check_over(&mut self, clients: &mut Clients) {
+        let playing = self.seats.iter().flatten().filter_map(|seat| seat.game.as_ref()).any(|game| game.outcome == Outcome::Playing);
+        if !self.over && !playing {
+            self.broadcast(clients, &Message::GameOver { results: self.results() });
+            self.over = true;
+        }
+    }
+}